@@ -0,0 +1,44 @@
+/// Pairs pages into the sheet sides of a booklet folded in the middle, left
+/// page first, padding the pages with blanks to a multiple of four.
+pub(crate) fn sheet_sides(pages: &[usize]) -> Vec<Vec<Option<usize>>> {
+    let len = pages.len().next_multiple_of(4);
+    let page = |index: usize| pages.get(index).copied();
+
+    (0..len / 4)
+        .flat_map(|sheet| {
+            let (outer, inner) = (2 * sheet, len - 1 - 2 * sheet);
+            [
+                vec![page(inner), page(outer)],
+                vec![page(outer + 1), page(inner - 1)],
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eight_pages() {
+        let pages = (0..8).collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                vec![Some(7), Some(0)],
+                vec![Some(1), Some(6)],
+                vec![Some(5), Some(2)],
+                vec![Some(3), Some(4)],
+            ],
+            sheet_sides(&pages)
+        );
+    }
+
+    #[test]
+    fn padded() {
+        assert_eq!(
+            vec![vec![None, Some(4)], vec![Some(5), None]],
+            sheet_sides(&[4, 5])
+        );
+        assert!(sheet_sides(&[]).is_empty());
+    }
+}
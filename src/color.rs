@@ -0,0 +1,385 @@
+use std::array::from_fn;
+
+use crate::halftone::{Halftone, Halftoner};
+
+// Linear sRGB to linear Adobe RGB (1998), both relative to D65.
+const SRGB_TO_ADOBE_RGB: [[f32; 3]; 3] = [
+    [0.7152, 0.2848, 0.0],
+    [0.0, 1.0, 0.0],
+    [0.0, 0.0412, 0.9588],
+];
+
+// Adobe RGB (1998) encoding gamma, 2 51/256.
+const ADOBE_RGB_GAMMA: f32 = 563.0 / 256.0;
+
+/// Raster colour mode, named after the IPP `pwg-raster-document-type-supported`
+/// keywords.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// `sgray_8`, 8-bit gray
+    #[default]
+    Sgray8,
+    /// `srgb_8`, 24-bit sRGB
+    Srgb8,
+    /// `sgray_1`, bilevel gray, 1 being white
+    Sgray1(Halftone),
+    /// `black_8`, 8-bit inverted gray, 255 being black
+    Black8,
+    /// `black_1`, bilevel black, 1 being black
+    Black1(Halftone),
+    /// `cmyk_8`, 32-bit chunky CMYK separated from the rendered RGB
+    Cmyk8(BlackGeneration),
+    /// `sgray_16`, 16-bit big-endian gray
+    Sgray16,
+    /// `srgb_16`, 48-bit big-endian sRGB
+    Srgb16,
+    /// `adobe-rgb_8`, 24-bit Adobe RGB (1998) converted from sRGB
+    AdobeRgb8,
+    /// `adobe-rgb_16`, 48-bit big-endian Adobe RGB (1998) converted from sRGB
+    AdobeRgb16,
+    /// `rgb_8`, 24-bit device RGB, the rendered values passed through
+    Rgb8,
+    /// `rgb_16`, 48-bit big-endian device RGB, the rendered values passed through
+    Rgb16,
+}
+
+/// Black generation and undercolour removal used when separating RGB into CMYK.
+///
+/// The gray component of a pixel is the ink common to cyan, magenta and yellow.
+/// Above `start` it is rescaled to the full range and `black` percent of it
+/// becomes black ink; `undercolor_removal` percent of that black is then taken
+/// out of cyan, magenta and yellow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlackGeneration {
+    pub start: u8,
+    pub black: u8,
+    pub undercolor_removal: u8,
+}
+
+impl Default for BlackGeneration {
+    fn default() -> Self {
+        Self {
+            start: 0,
+            black: 100,
+            undercolor_removal: 100,
+        }
+    }
+}
+
+impl BlackGeneration {
+    fn separate(&self, red: u8, green: u8, blue: u8) -> [u8; 4] {
+        let (cyan, magenta, yellow) = (255 - red as u32, 255 - green as u32, 255 - blue as u32);
+        let gray = cyan.min(magenta).min(yellow);
+        let start = self.start as u32;
+
+        let black = if gray > start {
+            (gray - start) * 255 / (255 - start) * (self.black.min(100) as u32) / 100
+        } else {
+            0
+        };
+        let removed = black * (self.undercolor_removal.min(100) as u32) / 100;
+
+        [
+            cyan.saturating_sub(removed) as u8,
+            magenta.saturating_sub(removed) as u8,
+            yellow.saturating_sub(removed) as u8,
+            black as u8,
+        ]
+    }
+}
+
+impl ColorMode {
+    pub(crate) fn bits_per_color(&self) -> usize {
+        match self {
+            ColorMode::Sgray1(_) | ColorMode::Black1(_) => 1,
+            ColorMode::Sgray8
+            | ColorMode::Srgb8
+            | ColorMode::Black8
+            | ColorMode::Cmyk8(_)
+            | ColorMode::AdobeRgb8
+            | ColorMode::Rgb8 => 8,
+            ColorMode::Sgray16 | ColorMode::Srgb16 | ColorMode::AdobeRgb16 | ColorMode::Rgb16 => 16,
+        }
+    }
+
+    pub(crate) fn num_colors(&self) -> usize {
+        match self {
+            ColorMode::Sgray8
+            | ColorMode::Sgray1(_)
+            | ColorMode::Black8
+            | ColorMode::Black1(_)
+            | ColorMode::Sgray16 => 1,
+            ColorMode::Srgb8
+            | ColorMode::Srgb16
+            | ColorMode::AdobeRgb8
+            | ColorMode::AdobeRgb16
+            | ColorMode::Rgb8
+            | ColorMode::Rgb16 => 3,
+            ColorMode::Cmyk8(_) => 4,
+        }
+    }
+
+    pub(crate) fn bits_per_pixel(&self) -> usize {
+        self.bits_per_color() * self.num_colors()
+    }
+
+    pub(crate) fn is_gray(&self) -> bool {
+        self.num_colors() == 1
+    }
+}
+
+/// Converts sRGB into Adobe RGB (1998) through linear light.
+struct AdobeRgb {
+    linear: [f32; 256],
+    // gamma encoding of the linear value scaled to 16 bits
+    encode: Vec<u16>,
+}
+
+impl AdobeRgb {
+    fn new() -> Self {
+        let linear = from_fn(|value| {
+            let value = value as f32 / 255.0;
+            if value <= 0.04045 {
+                value / 12.92
+            } else {
+                ((value + 0.055) / 1.055).powf(2.4)
+            }
+        });
+        let encode = (0..=u16::MAX)
+            .map(|value| {
+                ((value as f32 / 65535.0).powf(1.0 / ADOBE_RGB_GAMMA) * 65535.0).round() as u16
+            })
+            .collect();
+
+        Self { linear, encode }
+    }
+
+    fn convert(&self, red: u8, green: u8, blue: u8) -> [u16; 3] {
+        let srgb = [
+            self.linear[red as usize],
+            self.linear[green as usize],
+            self.linear[blue as usize],
+        ];
+        from_fn(|channel| {
+            let row = &SRGB_TO_ADOBE_RGB[channel];
+            let linear = row[0] * srgb[0] + row[1] * srgb[1] + row[2] * srgb[2];
+            self.encode[(linear.clamp(0.0, 1.0) * 65535.0).round() as usize]
+        })
+    }
+}
+
+/// Converts lines of pdfium BGR pixels into raster lines of the colour mode.
+pub(crate) struct LineConverter {
+    color: ColorMode,
+    gray: Vec<u8>,
+    halftoner: Option<Halftoner>,
+    adobe_rgb: Option<AdobeRgb>,
+}
+
+impl LineConverter {
+    pub(crate) fn new(color: ColorMode, width: usize) -> Self {
+        let halftoner = match color {
+            ColorMode::Sgray1(halftone) | ColorMode::Black1(halftone) => {
+                Some(Halftoner::new(halftone, width))
+            }
+            _ => None,
+        };
+
+        let adobe_rgb = match color {
+            ColorMode::AdobeRgb8 | ColorMode::AdobeRgb16 => Some(AdobeRgb::new()),
+            _ => None,
+        };
+
+        Self {
+            color,
+            gray: vec![0; if halftoner.is_some() { width } else { 0 }],
+            halftoner,
+            adobe_rgb,
+        }
+    }
+
+    pub(crate) fn start_page(&mut self) {
+        if let Some(halftoner) = &mut self.halftoner {
+            halftoner.start_page();
+        }
+    }
+
+    pub(crate) fn convert(&mut self, bgr: &[u8], line: &mut [u8]) {
+        let pixels = bgr.chunks_exact(3);
+        match self.color {
+            // gray rendering leaves all three channels equal
+            ColorMode::Sgray8 => pixels
+                .zip(line.iter_mut())
+                .for_each(|(pixel, gray)| *gray = pixel[0]),
+            ColorMode::Srgb8 | ColorMode::Rgb8 => {
+                pixels
+                    .zip(line.chunks_exact_mut(3))
+                    .for_each(|(pixel, rgb)| {
+                        rgb[0] = pixel[2];
+                        rgb[1] = pixel[1];
+                        rgb[2] = pixel[0];
+                    })
+            }
+            ColorMode::Cmyk8(black_generation) => {
+                pixels
+                    .zip(line.chunks_exact_mut(4))
+                    .for_each(|(pixel, cmyk)| {
+                        cmyk.copy_from_slice(
+                            &black_generation.separate(pixel[2], pixel[1], pixel[0]),
+                        )
+                    })
+            }
+            // 8-bit samples widen to 16 bits by repeating the byte, v * 257
+            ColorMode::Sgray16 => pixels
+                .zip(line.chunks_exact_mut(2))
+                .for_each(|(pixel, gray)| gray.fill(pixel[0])),
+            ColorMode::Srgb16 | ColorMode::Rgb16 => {
+                pixels
+                    .zip(line.chunks_exact_mut(6))
+                    .for_each(|(pixel, rgb)| {
+                        rgb[..2].fill(pixel[2]);
+                        rgb[2..4].fill(pixel[1]);
+                        rgb[4..].fill(pixel[0]);
+                    })
+            }
+            ColorMode::AdobeRgb8 | ColorMode::AdobeRgb16 => {
+                if let Some(adobe_rgb) = &self.adobe_rgb {
+                    let bytes_per_color = self.color.bits_per_color() / 8;
+                    pixels
+                        .zip(line.chunks_exact_mut(3 * bytes_per_color))
+                        .for_each(|(pixel, rgb)| {
+                            let converted = adobe_rgb.convert(pixel[2], pixel[1], pixel[0]);
+                            rgb.chunks_exact_mut(bytes_per_color)
+                                .zip(converted)
+                                .for_each(|(sample, value)| {
+                                    if bytes_per_color == 2 {
+                                        sample.copy_from_slice(&value.to_be_bytes());
+                                    } else {
+                                        sample[0] = ((value as u32 * 255 + 32767) / 65535) as u8;
+                                    }
+                                })
+                        })
+                }
+            }
+            ColorMode::Black8 => pixels
+                .zip(line.iter_mut())
+                .for_each(|(pixel, black)| *black = !pixel[0]),
+            ColorMode::Sgray1(_) | ColorMode::Black1(_) => {
+                pixels
+                    .zip(self.gray.iter_mut())
+                    .for_each(|(pixel, gray)| *gray = pixel[0]);
+                if let Some(halftoner) = &mut self.halftoner {
+                    halftoner.line(&self.gray, line);
+                }
+                if let ColorMode::Black1(_) = self.color {
+                    line.iter_mut().for_each(|bits| *bits = !*bits);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn srgb8_reorders_channels() {
+        let bgr = [0x01_u8, 0x02, 0x03, 0x0a, 0x0b, 0x0c];
+        let mut line = [0_u8; 6];
+        LineConverter::new(ColorMode::Srgb8, 2).convert(&bgr, &mut line);
+        assert_eq!([0x03_u8, 0x02, 0x01, 0x0c, 0x0b, 0x0a], line);
+    }
+
+    #[test]
+    fn sgray8_takes_one_channel() {
+        let bgr = [0x80_u8, 0x80, 0x80, 0xff, 0xff, 0xff];
+        let mut line = [0_u8; 2];
+        LineConverter::new(ColorMode::Sgray8, 2).convert(&bgr, &mut line);
+        assert_eq!([0x80_u8, 0xff], line);
+    }
+
+    #[test]
+    fn sgray1_packs_bits() {
+        let bgr = [0x00_u8, 0x00, 0x00, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00];
+        let mut line = [0_u8; 1];
+        LineConverter::new(ColorMode::Sgray1(Halftone::default()), 3).convert(&bgr, &mut line);
+        assert_eq!([0b01011111_u8], line);
+    }
+
+    #[test]
+    fn black_inverts_polarity() {
+        let bgr = [0x00_u8, 0x00, 0x00, 0xff, 0xff, 0xff, 0x40, 0x40, 0x40];
+
+        let mut line = [0_u8; 3];
+        LineConverter::new(ColorMode::Black8, 3).convert(&bgr, &mut line);
+        assert_eq!([0xff_u8, 0x00, 0xbf], line);
+
+        let mut line = [0_u8; 1];
+        LineConverter::new(ColorMode::Black1(Halftone::default()), 3).convert(&bgr, &mut line);
+        assert_eq!([0b10100000_u8], line);
+    }
+
+    #[test]
+    fn cmyk8_black_generation() {
+        let full = BlackGeneration::default();
+        assert_eq!([0, 0, 0, 0], full.separate(255, 255, 255));
+        assert_eq!([0, 0, 0, 255], full.separate(0, 0, 0));
+        assert_eq!([0, 255, 255, 0], full.separate(255, 0, 0));
+        assert_eq!([0, 64, 128, 127], full.separate(128, 64, 0));
+
+        let none = BlackGeneration {
+            black: 0,
+            ..Default::default()
+        };
+        assert_eq!([255, 255, 255, 0], none.separate(0, 0, 0));
+
+        let skeleton = BlackGeneration {
+            start: 128,
+            black: 100,
+            undercolor_removal: 50,
+        };
+        assert_eq!([128, 128, 128, 0], skeleton.separate(127, 127, 127));
+        assert_eq!([128, 128, 128, 255], skeleton.separate(0, 0, 0));
+    }
+
+    #[test]
+    fn cmyk8_reads_bgr() {
+        let bgr = [0x00_u8, 0x00, 0xff];
+        let mut line = [0_u8; 4];
+        LineConverter::new(ColorMode::Cmyk8(BlackGeneration::default()), 1)
+            .convert(&bgr, &mut line);
+        assert_eq!([0x00_u8, 0xff, 0xff, 0x00], line);
+    }
+
+    #[test]
+    fn sixteen_bit_samples() {
+        let bgr = [0x01_u8, 0x02, 0x03];
+
+        let mut line = [0_u8; 2];
+        LineConverter::new(ColorMode::Sgray16, 1).convert(&bgr, &mut line);
+        assert_eq!([0x01_u8, 0x01], line);
+
+        let mut line = [0_u8; 6];
+        LineConverter::new(ColorMode::Srgb16, 1).convert(&bgr, &mut line);
+        assert_eq!([0x03_u8, 0x03, 0x02, 0x02, 0x01, 0x01], line);
+    }
+
+    #[test]
+    fn adobe_rgb_conversion() {
+        let adobe_rgb = AdobeRgb::new();
+        assert_eq!([0, 0, 0], adobe_rgb.convert(0, 0, 0));
+        assert_eq!([65535, 65535, 65535], adobe_rgb.convert(255, 255, 255));
+
+        // sRGB green lies well inside Adobe RGB
+        let bgr = [0x00_u8, 0xff, 0x00];
+        let mut line = [0_u8; 3];
+        LineConverter::new(ColorMode::AdobeRgb8, 1).convert(&bgr, &mut line);
+        assert_eq!([144_u8, 255, 60], line);
+
+        let mut line = [0_u8; 6];
+        LineConverter::new(ColorMode::AdobeRgb16, 1).convert(&bgr, &mut line);
+        assert_eq!(255, line[2]);
+        assert_eq!(144, line[0]);
+    }
+}
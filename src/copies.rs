@@ -0,0 +1,185 @@
+use crate::{duplex::Sides, error::Error};
+
+/// How multiple copies are produced, IPP `multiple-document-handling`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Collation {
+    /// The printer makes the copies from PWG `NumCopies`. URF has no such
+    /// field, so URF output repeats the pages collated.
+    #[default]
+    Printer,
+    /// The pages are repeated as whole documents, 1 2 3 1 2 3.
+    Collated,
+    /// Each sheet is repeated before the next, 1 1 2 2 3 3.
+    Uncollated,
+}
+
+/// Number of copies, IPP `copies`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Copies {
+    count: u32,
+    collation: Collation,
+}
+
+impl Copies {
+    pub fn new(count: u32, collation: Collation) -> Result<Self, Error> {
+        if count > 0 {
+            Ok(Self { count, collation })
+        } else {
+            Err(Error::InvalidCopies(count))
+        }
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn collation(&self) -> Collation {
+        self.collation
+    }
+
+    /// Copies left for the printer to make, PWG `NumCopies`.
+    pub(crate) fn printer_count(&self) -> u32 {
+        match self.collation {
+            Collation::Printer => self.count,
+            Collation::Collated | Collation::Uncollated => 1,
+        }
+    }
+}
+
+impl Default for Copies {
+    fn default() -> Self {
+        Self {
+            count: 1,
+            collation: Collation::Printer,
+        }
+    }
+}
+
+/// Sheet sides of the output in order, `None` being a blank side.
+pub(crate) fn sequence<T: Clone>(
+    pages: &[T],
+    copies: Copies,
+    sides: Sides,
+    pad_odd_pages: bool,
+) -> Vec<Option<T>> {
+    let sheet_sides = match sides {
+        Sides::OneSided => 1,
+        Sides::TwoSidedLongEdge | Sides::TwoSidedShortEdge => 2,
+    };
+    let pad = |sequence: &mut Vec<Option<T>>| {
+        if pad_odd_pages && !sequence.len().is_multiple_of(sheet_sides) {
+            sequence.push(None);
+        }
+    };
+
+    let mut sequence = Vec::with_capacity(pages.len() * copies.count as usize + 1);
+    match copies.collation {
+        Collation::Printer => {
+            sequence.extend(pages.iter().cloned().map(Some));
+            pad(&mut sequence);
+        }
+        Collation::Collated => {
+            for _ in 0..copies.count {
+                sequence.extend(pages.iter().cloned().map(Some));
+                pad(&mut sequence);
+            }
+        }
+        Collation::Uncollated => {
+            // repeating single pages would put copies on the back of each
+            // other, so repeat whole sheets and always fill the last one
+            for sheet in pages.chunks(sheet_sides) {
+                for _ in 0..copies.count {
+                    sequence.extend(sheet.iter().cloned().map(Some));
+                    sequence.resize(sequence.len() + sheet_sides - sheet.len(), None);
+                }
+            }
+        }
+    }
+    sequence
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGES: [usize; 3] = [0, 1, 2];
+
+    #[test]
+    fn printer_copies() {
+        let copies = Copies::new(3, Collation::Printer).unwrap();
+        assert_eq!(
+            vec![Some(0), Some(1), Some(2)],
+            sequence(&PAGES, copies, Sides::OneSided, true)
+        );
+        assert_eq!(
+            vec![Some(0), Some(1), Some(2), None],
+            sequence(&PAGES, copies, Sides::TwoSidedLongEdge, true)
+        );
+    }
+
+    #[test]
+    fn collated() {
+        let copies = Copies::new(2, Collation::Collated).unwrap();
+        assert_eq!(
+            vec![Some(0), Some(1), Some(2), Some(0), Some(1), Some(2)],
+            sequence(&PAGES, copies, Sides::TwoSidedLongEdge, false)
+        );
+        assert_eq!(
+            vec![
+                Some(0),
+                Some(1),
+                Some(2),
+                None,
+                Some(0),
+                Some(1),
+                Some(2),
+                None
+            ],
+            sequence(&PAGES, copies, Sides::TwoSidedLongEdge, true)
+        );
+    }
+
+    #[test]
+    fn uncollated() {
+        let copies = Copies::new(2, Collation::Uncollated).unwrap();
+        assert_eq!(
+            vec![Some(0), Some(0), Some(1), Some(1), Some(2), Some(2)],
+            sequence(&PAGES, copies, Sides::OneSided, false)
+        );
+        assert_eq!(
+            vec![
+                Some(0),
+                Some(1),
+                Some(0),
+                Some(1),
+                Some(2),
+                None,
+                Some(2),
+                None
+            ],
+            sequence(&PAGES, copies, Sides::TwoSidedShortEdge, false)
+        );
+    }
+
+    #[test]
+    fn printer_count() {
+        assert_eq!(
+            3,
+            Copies::new(3, Collation::Printer).unwrap().printer_count()
+        );
+        assert_eq!(
+            1,
+            Copies::new(3, Collation::Uncollated)
+                .unwrap()
+                .printer_count()
+        );
+    }
+
+    #[test]
+    fn no_copies() {
+        assert!(matches!(
+            Copies::new(0, Collation::Collated),
+            Err(Error::InvalidCopies(0))
+        ));
+    }
+}
@@ -0,0 +1,107 @@
+/// Sides printed on each sheet, IPP `sides`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Sides {
+    #[default]
+    OneSided,
+    TwoSidedLongEdge,
+    /// Tumbled, the back side is upside down relative to the front.
+    TwoSidedShortEdge,
+}
+
+/// How the printer images the back side of a sheet, IPP
+/// `pwg-raster-document-sheet-back` (URF `DM1` to `DM4`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SheetBack {
+    #[default]
+    Normal,
+    Flipped,
+    Rotated,
+    ManualTumble,
+}
+
+/// Flips of a page raster, PWG `CrossFeedTransform` and `FeedTransform` of -1.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Transform {
+    pub flip_cross_feed: bool,
+    pub flip_feed: bool,
+}
+
+impl Transform {
+    const NONE: Transform = Transform {
+        flip_cross_feed: false,
+        flip_feed: false,
+    };
+
+    const ROTATE_180: Transform = Transform {
+        flip_cross_feed: true,
+        flip_feed: true,
+    };
+
+    /// Transform of the page with the zero-based index, back sides being odd.
+    pub(crate) fn new(sides: Sides, sheet_back: SheetBack, page_index: usize) -> Self {
+        if page_index.is_multiple_of(2) {
+            return Self::NONE;
+        }
+
+        let tumble = match sides {
+            Sides::OneSided => return Self::NONE,
+            Sides::TwoSidedLongEdge => false,
+            Sides::TwoSidedShortEdge => true,
+        };
+
+        match sheet_back {
+            SheetBack::Normal => Self::NONE,
+            SheetBack::Flipped => Transform {
+                flip_cross_feed: tumble,
+                flip_feed: !tumble,
+            },
+            SheetBack::Rotated if !tumble => Self::ROTATE_180,
+            SheetBack::ManualTumble if tumble => Self::ROTATE_180,
+            SheetBack::Rotated | SheetBack::ManualTumble => Self::NONE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn front_sides_are_untouched() {
+        for sheet_back in [
+            SheetBack::Normal,
+            SheetBack::Flipped,
+            SheetBack::Rotated,
+            SheetBack::ManualTumble,
+        ] {
+            assert_eq!(
+                Transform::NONE,
+                Transform::new(Sides::TwoSidedLongEdge, sheet_back, 0)
+            );
+            assert_eq!(
+                Transform::NONE,
+                Transform::new(Sides::OneSided, sheet_back, 1)
+            );
+        }
+    }
+
+    #[test]
+    fn back_sides() {
+        let long = |sheet_back| Transform::new(Sides::TwoSidedLongEdge, sheet_back, 1);
+        let short = |sheet_back| Transform::new(Sides::TwoSidedShortEdge, sheet_back, 3);
+
+        assert_eq!(Transform::NONE, long(SheetBack::Normal));
+        assert_eq!(Transform::NONE, short(SheetBack::Normal));
+
+        assert!(long(SheetBack::Flipped).flip_feed);
+        assert!(!long(SheetBack::Flipped).flip_cross_feed);
+        assert!(short(SheetBack::Flipped).flip_cross_feed);
+        assert!(!short(SheetBack::Flipped).flip_feed);
+
+        assert_eq!(Transform::ROTATE_180, long(SheetBack::Rotated));
+        assert_eq!(Transform::NONE, short(SheetBack::Rotated));
+
+        assert_eq!(Transform::NONE, long(SheetBack::ManualTumble));
+        assert_eq!(Transform::ROTATE_180, short(SheetBack::ManualTumble));
+    }
+}
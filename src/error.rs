@@ -1,10 +1,36 @@
 use pdfium_render::prelude::PdfiumError;
 use thiserror::Error;
 
+use crate::color::ColorMode;
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("{0}")]
     Compose(#[from] std::io::Error),
+    #[error("invalid number of copies {0}")]
+    InvalidCopies(u32),
+    #[error("invalid media size")]
+    InvalidMediaSize,
+    #[error("invalid page ranges {0:?}")]
+    InvalidPageRanges(String),
+    #[error("invalid resolution {0} dpi")]
+    InvalidResolution(u32),
+    #[error("no pages selected")]
+    NoPagesSelected,
+    #[error("password required")]
+    PasswordRequired,
     #[error("{0}")]
     Render(#[from] PdfiumError),
+    #[error("pdfium is linked statically")]
+    StaticallyLinked,
+    #[error("unequal resolutions {0}x{1} dpi")]
+    UnequalResolutions(u32, u32),
+    #[error("unsupported colour mode {0:?}")]
+    UnsupportedColorMode(ColorMode),
+    #[error("unsupported number up {0}")]
+    UnsupportedNumberUp(usize),
+    #[error("unsupported resolution {0} dpi")]
+    UnsupportedResolution(u32),
+    #[error("wrong password")]
+    WrongPassword,
 }
@@ -0,0 +1,148 @@
+/// Method reducing 8-bit gray to bilevel pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Halftone {
+    /// Pixels darker than the level become black.
+    Threshold(u8),
+    /// 8x8 ordered Bayer dither.
+    Bayer,
+    /// Floyd–Steinberg error diffusion.
+    FloydSteinberg,
+}
+
+impl Default for Halftone {
+    fn default() -> Self {
+        Self::Threshold(128)
+    }
+}
+
+const BAYER_8X8: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+/// Halftones a page line by line into packed bits, 1 being white.
+pub(crate) struct Halftoner {
+    halftone: Halftone,
+    line_index: usize,
+    // Floyd–Steinberg errors for the current and the next line, padded by one
+    // pixel on both sides
+    errors: Vec<i16>,
+    next_errors: Vec<i16>,
+}
+
+impl Halftoner {
+    pub(crate) fn new(halftone: Halftone, width: usize) -> Self {
+        let errors = match halftone {
+            Halftone::FloydSteinberg => width + 2,
+            _ => 0,
+        };
+
+        Self {
+            halftone,
+            line_index: 0,
+            errors: vec![0; errors],
+            next_errors: vec![0; errors],
+        }
+    }
+
+    pub(crate) fn start_page(&mut self) {
+        self.line_index = 0;
+        self.errors.fill(0);
+        self.next_errors.fill(0);
+    }
+
+    pub(crate) fn line(&mut self, gray: &[u8], bits: &mut [u8]) {
+        // padding bits past the last pixel stay white
+        bits.fill(0xff);
+
+        match self.halftone {
+            Halftone::Threshold(level) => gray
+                .iter()
+                .enumerate()
+                .filter(|(_, &value)| value < level)
+                .for_each(|(x, _)| set_black(bits, x)),
+            Halftone::Bayer => {
+                let row = &BAYER_8X8[self.line_index % 8];
+                gray.iter()
+                    .enumerate()
+                    .filter(|(x, &value)| value < row[x % 8] * 4 + 2)
+                    .for_each(|(x, _)| set_black(bits, x))
+            }
+            Halftone::FloydSteinberg => self.diffuse(gray, bits),
+        }
+
+        self.line_index += 1;
+    }
+
+    fn diffuse(&mut self, gray: &[u8], bits: &mut [u8]) {
+        for (x, &value) in gray.iter().enumerate() {
+            let value = (value as i16 + self.errors[x + 1]).clamp(0, 255);
+            let error = if value < 128 {
+                set_black(bits, x);
+                value
+            } else {
+                value - 255
+            };
+
+            self.errors[x + 2] += error * 7 / 16;
+            self.next_errors[x] += error * 3 / 16;
+            self.next_errors[x + 1] += error * 5 / 16;
+            self.next_errors[x + 2] += error / 16;
+        }
+
+        std::mem::swap(&mut self.errors, &mut self.next_errors);
+        self.next_errors.fill(0);
+    }
+}
+
+fn set_black(bits: &mut [u8], x: usize) {
+    bits[x / 8] &= !(0x80 >> (x % 8));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn white_pixels(halftone: Halftone, value: u8) -> u32 {
+        let mut halftoner = Halftoner::new(halftone, 8);
+        let mut bits = [0_u8; 1];
+        (0..8)
+            .map(|_| {
+                halftoner.line(&[value; 8], &mut bits);
+                bits[0].count_ones()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn threshold() {
+        let mut halftoner = Halftoner::new(Halftone::Threshold(100), 10);
+        let mut bits = [0_u8; 2];
+        halftoner.line(&[0, 99, 100, 255, 0, 0, 0, 0, 0, 50], &mut bits);
+        assert_eq!([0b00110000_u8, 0b00111111], bits);
+    }
+
+    #[test]
+    fn extremes_stay_solid() {
+        for halftone in [
+            Halftone::default(),
+            Halftone::Bayer,
+            Halftone::FloydSteinberg,
+        ] {
+            assert_eq!(64, white_pixels(halftone, 255));
+            assert_eq!(0, white_pixels(halftone, 0));
+        }
+    }
+
+    #[test]
+    fn mid_gray_is_half_white() {
+        assert_eq!(32, white_pixels(Halftone::Bayer, 128));
+        assert!((28..=36).contains(&white_pixels(Halftone::FloydSteinberg, 128)));
+    }
+}
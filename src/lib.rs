@@ -1,11 +1,44 @@
+mod booklet;
+
+mod color;
+pub use color::{BlackGeneration, ColorMode};
+
+mod copies;
+pub use copies::{Collation, Copies};
+
+mod duplex;
+pub use duplex::{SheetBack, Sides};
+
 mod error;
 pub use error::*;
 
+mod halftone;
+pub use halftone::Halftone;
+
+mod media;
+pub use media::{CustomMedia, MediaSize, MediaUnit};
+
+mod number_up;
+pub use number_up::{Direction, NumberUp};
+
+mod page_range;
+pub use page_range::PageRanges;
+
+mod poster;
+pub use poster::Poster;
+
 mod pwg;
 
 mod render;
-pub use render::{render, Format, Orientation, Resolution};
+pub use render::PDFIUM_LIBRARY_VAR;
+pub use render::{
+    render, render_stream, render_to_async_writer, render_to_writer, Format, Options, Orientation,
+    Renderer, Resolution,
+};
 
 mod rle;
 
+mod scaling;
+pub use scaling::Scaling;
+
 mod urf;
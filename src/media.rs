@@ -0,0 +1,177 @@
+use std::borrow::Cow;
+
+use crate::error::Error;
+
+// PWG 5101.1 expresses media dimensions in hundredths of millimetre, which
+// keeps both metric and imperial sizes exact.
+const HUNDREDTHS_MM_PER_INCH: usize = 2540;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MediaUnit {
+    Millimetres,
+    Inches,
+}
+
+/// Custom media dimensions, created through [`MediaSize::custom`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CustomMedia {
+    width: usize,
+    height: usize,
+    unit: MediaUnit,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MediaSize {
+    IsoA3,
+    #[default]
+    IsoA4,
+    IsoA5,
+    IsoB5,
+    JisB5,
+    NaLetter,
+    NaLegal,
+    NaExecutive,
+    NaLedger,
+    Custom(CustomMedia),
+}
+
+impl MediaSize {
+    /// Portrait media of the given width and height, e.g. `custom(100.0, 150.0,
+    /// MediaUnit::Millimetres)` for a 100x150mm label.
+    pub fn custom(width: f64, height: f64, unit: MediaUnit) -> Result<Self, Error> {
+        let scale = match unit {
+            MediaUnit::Millimetres => 100.0,
+            MediaUnit::Inches => HUNDREDTHS_MM_PER_INCH as f64,
+        };
+
+        let to_hundredths_mm = |value: f64| {
+            let value = (value * scale).round();
+            (value.is_finite() && value >= 1.0 && value <= u32::MAX as f64)
+                .then_some(value as usize)
+        };
+
+        match (to_hundredths_mm(width), to_hundredths_mm(height)) {
+            (Some(width), Some(height)) => Ok(Self::Custom(CustomMedia {
+                width,
+                height,
+                unit,
+            })),
+            _ => Err(Error::InvalidMediaSize),
+        }
+    }
+
+    /// PWG 5101.1 self-describing media name, as written into `PageSizeName`.
+    pub fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed(match self {
+            MediaSize::IsoA3 => "iso_a3_297x420mm",
+            MediaSize::IsoA4 => "iso_a4_210x297mm",
+            MediaSize::IsoA5 => "iso_a5_148x210mm",
+            MediaSize::IsoB5 => "iso_b5_176x250mm",
+            MediaSize::JisB5 => "jis_b5_182x257mm",
+            MediaSize::NaLetter => "na_letter_8.5x11in",
+            MediaSize::NaLegal => "na_legal_8.5x14in",
+            MediaSize::NaExecutive => "na_executive_7.25x10.5in",
+            MediaSize::NaLedger => "na_ledger_11x17in",
+            MediaSize::Custom(custom) => {
+                let size = custom.size_name();
+                return Cow::Owned(format!("custom_{size}_{size}"));
+            }
+        })
+    }
+
+    /// Portrait width and height in hundredths of millimetre.
+    pub fn dimensions(&self) -> (usize, usize) {
+        match self {
+            MediaSize::IsoA3 => (29700, 42000),
+            MediaSize::IsoA4 => (21000, 29700),
+            MediaSize::IsoA5 => (14800, 21000),
+            MediaSize::IsoB5 => (17600, 25000),
+            MediaSize::JisB5 => (18200, 25700),
+            MediaSize::NaLetter => (21590, 27940),
+            MediaSize::NaLegal => (21590, 35560),
+            MediaSize::NaExecutive => (18415, 26670),
+            MediaSize::NaLedger => (27940, 43180),
+            MediaSize::Custom(custom) => (custom.width, custom.height),
+        }
+    }
+
+    pub(crate) fn pixels(
+        &self,
+        resolution_width: usize,
+        resolution_height: usize,
+    ) -> (usize, usize) {
+        let (width, height) = self.dimensions();
+        (
+            to_pixels(width, resolution_width),
+            to_pixels(height, resolution_height),
+        )
+    }
+
+    pub(crate) fn points(&self) -> (usize, usize) {
+        let (width, height) = self.dimensions();
+        (to_pixels(width, 72), to_pixels(height, 72))
+    }
+}
+
+impl CustomMedia {
+    fn size_name(&self) -> String {
+        let (unit, divisor) = match self.unit {
+            MediaUnit::Millimetres => ("mm", 100.0),
+            MediaUnit::Inches => ("in", HUNDREDTHS_MM_PER_INCH as f64),
+        };
+
+        format!(
+            "{}x{}{unit}",
+            format_dimension(self.width as f64 / divisor),
+            format_dimension(self.height as f64 / divisor)
+        )
+    }
+}
+
+// PWG names use the shortest decimal form, e.g. `8.5` or `100`
+fn format_dimension(value: f64) -> String {
+    let formatted = format!("{value:.3}");
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+fn to_pixels(hundredths_mm: usize, dpi: usize) -> usize {
+    (hundredths_mm * dpi + HUNDREDTHS_MM_PER_INCH / 2) / HUNDREDTHS_MM_PER_INCH
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MediaSize, MediaUnit};
+
+    #[test]
+    fn iso_a4_pixels() {
+        assert_eq!((2480, 3508), MediaSize::IsoA4.pixels(300, 300));
+        assert_eq!((4961, 7016), MediaSize::IsoA4.pixels(600, 600));
+        assert_eq!((595, 842), MediaSize::IsoA4.points());
+    }
+
+    #[test]
+    fn na_letter_pixels() {
+        assert_eq!((2550, 3300), MediaSize::NaLetter.pixels(300, 300));
+        assert_eq!((612, 792), MediaSize::NaLetter.points());
+    }
+
+    #[test]
+    fn custom_names() {
+        let label = MediaSize::custom(100.0, 150.0, MediaUnit::Millimetres).unwrap();
+        assert_eq!("custom_100x150mm_100x150mm", label.name());
+        assert_eq!((1181, 1772), label.pixels(300, 300));
+
+        let ticket = MediaSize::custom(4.0, 6.5, MediaUnit::Inches).unwrap();
+        assert_eq!("custom_4x6.5in_4x6.5in", ticket.name());
+        assert_eq!((1200, 1950), ticket.pixels(300, 300));
+    }
+
+    #[test]
+    fn custom_invalid() {
+        assert!(MediaSize::custom(0.0, 150.0, MediaUnit::Millimetres).is_err());
+        assert!(MediaSize::custom(100.0, f64::NAN, MediaUnit::Inches).is_err());
+    }
+}
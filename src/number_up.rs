@@ -0,0 +1,205 @@
+use crate::{
+    error::Error,
+    render::{Orientation, PagePixels},
+    scaling::Area,
+};
+
+/// Order of the pages on a sheet side, IPP `presentation-direction-number-up`,
+/// as the pages are read, i.e. with the sheet turned to show them upright.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Direction {
+    #[default]
+    ToRightToBottom,
+    ToBottomToRight,
+    ToLeftToBottom,
+    ToBottomToLeft,
+}
+
+/// Pages per sheet side, IPP `number-up`. The cells of 2 and 6 up are
+/// landscape, so their pages are rotated per cell, overriding orientation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NumberUp {
+    count: usize,
+    pub direction: Direction,
+    /// Draws a line around each page, IPP `page-border` `single`.
+    pub border: bool,
+    /// Space between the pages in millimetres.
+    pub gutter: f64,
+}
+
+impl NumberUp {
+    /// Accepts 1, 2, 4, 6 or 9 pages per sheet side.
+    pub fn new(count: usize) -> Result<Self, Error> {
+        match count {
+            1 | 2 | 4 | 6 | 9 => Ok(Self {
+                count,
+                ..Default::default()
+            }),
+            _ => Err(Error::UnsupportedNumberUp(count)),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Columns and rows on the portrait raster; 2 and 6 up stack landscape
+    /// cells, which pages are always rotated to fit.
+    fn grid(&self) -> (usize, usize) {
+        match self.count {
+            2 => (1, 2),
+            4 => (2, 2),
+            6 => (2, 3),
+            9 => (3, 3),
+            _ => (1, 1),
+        }
+    }
+
+    /// Areas of the pages on the raster in presentation order, for pages
+    /// rotated by the resolved `orientation`.
+    pub(crate) fn cells(&self, page: &PagePixels, orientation: Orientation) -> Vec<Area> {
+        let (columns, rows) = self.grid();
+        // clockwise quarter turns of the pages, which the reader undoes
+        let turns = match orientation {
+            Orientation::Rotated => 1,
+            Orientation::ReversePortrait => 2,
+            Orientation::ReverseLandscape => 3,
+            Orientation::Portrait | Orientation::Landscape | Orientation::Auto => 0,
+        };
+        let (read_columns, read_rows) = match turns % 2 {
+            0 => (columns, rows),
+            _ => (rows, columns),
+        };
+        let gutter = |resolution: usize| (self.gutter * resolution as f64 / 25.4).round() as usize;
+        let (gutter_x, gutter_y) = (
+            gutter(page.resolution_width),
+            gutter(page.resolution_height),
+        );
+        let width = page.width.saturating_sub((columns - 1) * gutter_x) / columns;
+        let height = page.height.saturating_sub((rows - 1) * gutter_y) / rows;
+
+        (0..self.count)
+            .map(|index| {
+                let (column, row) = match self.direction {
+                    Direction::ToRightToBottom => (index % read_columns, index / read_columns),
+                    Direction::ToBottomToRight => (index / read_rows, index % read_rows),
+                    Direction::ToLeftToBottom => (
+                        read_columns - 1 - index % read_columns,
+                        index / read_columns,
+                    ),
+                    Direction::ToBottomToLeft => {
+                        (read_columns - 1 - index / read_rows, index % read_rows)
+                    }
+                };
+                // from the grid as read back onto the raster
+                let (column, row) = match turns {
+                    1 => (columns - 1 - row, column),
+                    2 => (columns - 1 - column, rows - 1 - row),
+                    3 => (row, rows - 1 - column),
+                    _ => (column, row),
+                };
+                Area {
+                    x: column * (width + gutter_x),
+                    y: row * (height + gutter_y),
+                    width,
+                    height,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Default for NumberUp {
+    fn default() -> Self {
+        Self {
+            count: 1,
+            direction: Direction::default(),
+            border: false,
+            gutter: 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn origins(number_up: &NumberUp, orientation: Orientation) -> Vec<(usize, usize)> {
+        number_up
+            .cells(&PagePixels::a4(), orientation)
+            .iter()
+            .map(|area| (area.x, area.y))
+            .collect()
+    }
+
+    #[test]
+    fn four_up_directions() {
+        let mut number_up = NumberUp::new(4).unwrap();
+        assert_eq!(
+            vec![(0, 0), (1240, 0), (0, 1754), (1240, 1754)],
+            origins(&number_up, Orientation::Portrait)
+        );
+
+        number_up.direction = Direction::ToBottomToLeft;
+        assert_eq!(
+            vec![(1240, 0), (1240, 1754), (0, 0), (0, 1754)],
+            origins(&number_up, Orientation::Portrait)
+        );
+
+        // read with the sheet turned clockwise, the bottom left cell comes first
+        number_up.direction = Direction::ToRightToBottom;
+        assert_eq!(
+            vec![(0, 1754), (0, 0), (1240, 1754), (1240, 0)],
+            origins(&number_up, Orientation::ReverseLandscape)
+        );
+    }
+
+    #[test]
+    fn six_up_order() {
+        let number_up = NumberUp::new(6).unwrap();
+        // the pages rotated into the cells are read with the sheet turned
+        // counter-clockwise, the right column becoming the top row
+        assert_eq!(
+            vec![
+                (1240, 0),
+                (1240, 1169),
+                (1240, 2338),
+                (0, 0),
+                (0, 1169),
+                (0, 2338)
+            ],
+            origins(&number_up, Orientation::Rotated)
+        );
+        // landscape pages fit the cells as they are
+        assert_eq!(
+            vec![
+                (0, 0),
+                (1240, 0),
+                (0, 1169),
+                (1240, 1169),
+                (0, 2338),
+                (1240, 2338)
+            ],
+            origins(&number_up, Orientation::Portrait)
+        );
+    }
+
+    #[test]
+    fn gutter() {
+        let mut number_up = NumberUp::new(2).unwrap();
+        number_up.gutter = 25.4;
+        let cells = number_up.cells(&PagePixels::a4(), Orientation::Portrait);
+
+        assert_eq!(2480, cells[0].width);
+        assert_eq!((3508 - 300) / 2, cells[0].height);
+        assert_eq!(cells[0].height + 300, cells[1].y);
+    }
+
+    #[test]
+    fn unsupported() {
+        assert!(matches!(
+            NumberUp::new(3),
+            Err(Error::UnsupportedNumberUp(3))
+        ));
+    }
+}
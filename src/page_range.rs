@@ -0,0 +1,92 @@
+use std::{ops::RangeInclusive, str::FromStr};
+
+use crate::error::Error;
+
+/// Pages to render, IPP `page-ranges`, e.g. `1-3,7,10-`. Page numbers start
+/// at 1 and open ranges run to the last page. The default selects all pages.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PageRanges(Vec<RangeInclusive<usize>>);
+
+impl PageRanges {
+    pub fn new(ranges: impl IntoIterator<Item = RangeInclusive<usize>>) -> Result<Self, Error> {
+        let ranges = ranges.into_iter().collect::<Vec<_>>();
+        match ranges
+            .iter()
+            .find(|range| *range.start() == 0 || range.is_empty())
+        {
+            Some(range) => Err(Error::InvalidPageRanges(format!(
+                "{}-{}",
+                range.start(),
+                range.end()
+            ))),
+            None => Ok(Self(ranges)),
+        }
+    }
+
+    /// Zero-based indices of the selected pages in document order; ranges
+    /// beyond the last page of a document select nothing, which is an error.
+    pub(crate) fn pages(&self, page_count: usize) -> Result<Vec<usize>, Error> {
+        let pages = (0..page_count)
+            .filter(|index| {
+                self.0.is_empty() || self.0.iter().any(|range| range.contains(&(index + 1)))
+            })
+            .collect::<Vec<_>>();
+        match pages.is_empty() && page_count > 0 {
+            true => Err(Error::NoPagesSelected),
+            false => Ok(pages),
+        }
+    }
+}
+
+impl FromStr for PageRanges {
+    type Err = Error;
+
+    fn from_str(ranges: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidPageRanges(ranges.to_string());
+        let number = |page: &str| page.trim().parse::<usize>().map_err(|_| invalid());
+
+        let ranges = ranges
+            .split(',')
+            .map(|range| match range.split_once('-') {
+                Some((first, last)) if last.trim().is_empty() => Ok(number(first)?..=usize::MAX),
+                Some((first, last)) => Ok(number(first)?..=number(last)?),
+                None => number(range).map(|page| page..=page),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::new(ranges).map_err(|_| invalid())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let ranges = "1-3,7, 10-".parse::<PageRanges>().unwrap();
+        assert_eq!(vec![0, 1, 2, 6, 9, 10, 11], ranges.pages(12).unwrap());
+        assert_eq!(vec![0, 1], ranges.pages(2).unwrap());
+    }
+
+    #[test]
+    fn all_pages() {
+        assert_eq!(vec![0, 1, 2], PageRanges::default().pages(3).unwrap());
+    }
+
+    #[test]
+    fn none_selected() {
+        let ranges = "10-".parse::<PageRanges>().unwrap();
+        assert!(matches!(ranges.pages(5), Err(Error::NoPagesSelected)));
+    }
+
+    #[test]
+    fn invalid() {
+        for ranges in ["", "0", "3-1", "1,,2", "a-3", "-3"] {
+            assert!(matches!(
+                ranges.parse::<PageRanges>(),
+                Err(Error::InvalidPageRanges(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{render::PagePixels, scaling::Area};
+
+/// Tiles each page at actual size across as many sheets as it needs, e.g. an
+/// A1 page across A4 sheets.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Poster {
+    /// Margin in millimetres repeated on neighbouring tiles for gluing.
+    pub overlap: f64,
+    /// Marks where to cut each tile's overlap off before gluing it onto the
+    /// tile to the left or above.
+    pub crop_marks: bool,
+}
+
+/// Tile of a poster page, the position of its top left corner on the page.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct Tile {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Poster {
+    fn overlap(&self, page: &PagePixels) -> (usize, usize) {
+        let pixels = |resolution: usize| (self.overlap * resolution as f64 / 25.4).round() as usize;
+        // a tile needs to advance
+        (
+            pixels(page.resolution_width).min(page.width - 1),
+            pixels(page.resolution_height).min(page.height - 1),
+        )
+    }
+
+    /// Tiles covering a page of `width` by `height` pixels in reading order.
+    pub(crate) fn tiles(&self, width: usize, height: usize, page: &PagePixels) -> Vec<Tile> {
+        let (overlap_x, overlap_y) = self.overlap(page);
+        let origins = |size: usize, tile: usize, overlap: usize, resolution: usize| {
+            let step = tile - overlap;
+            // pages a rounding error larger than whole tiles, e.g. A2 on A4,
+            // lose up to a millimetre instead of spilling onto another tile
+            let slack = resolution / 25;
+            let count = size.saturating_sub(overlap + slack).div_ceil(step).max(1);
+            (0..count).map(move |index| index * step)
+        };
+
+        origins(height, page.height, overlap_y, page.resolution_height)
+            .flat_map(|y| {
+                origins(width, page.width, overlap_x, page.resolution_width)
+                    .map(move |x| Tile { x, y })
+            })
+            .collect()
+    }
+
+    /// Short lines on the sheet edges at the inner end of the overlap of a tile
+    /// that is not in the first column or row.
+    pub(crate) fn crop_marks(&self, tile: Tile, page: &PagePixels) -> Vec<Area> {
+        if !self.crop_marks {
+            return Vec::new();
+        }
+
+        // a quarter of a millimetre thick, 5 millimetres long
+        let (overlap_x, overlap_y) = self.overlap(page);
+        let thickness = (page.resolution_width / 100).max(1);
+        let length = (page.resolution_height * 50 / 254).min(page.height / 2);
+        let mut marks = Vec::new();
+        if tile.x > 0 && overlap_x > 0 {
+            let x = overlap_x.min(page.width - thickness);
+            marks.extend([0, page.height - length].map(|y| Area {
+                x,
+                y,
+                width: thickness,
+                height: length,
+            }));
+        }
+        if tile.y > 0 && overlap_y > 0 {
+            let length = (page.resolution_width * 50 / 254).min(page.width / 2);
+            let y = overlap_y.min(page.height - thickness);
+            marks.extend([0, page.width - length].map(|x| Area {
+                x,
+                y,
+                width: length,
+                height: thickness,
+            }));
+        }
+        marks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::MediaSize;
+
+    #[test]
+    fn a2_on_a4() {
+        let (a2_width, a2_height) = MediaSize::IsoA4.pixels(600, 600);
+        assert_eq!((4961, 7016), (a2_width, a2_height));
+        let tiles = Poster::default().tiles(a2_width, a2_height, &PagePixels::a4());
+
+        assert_eq!(4, tiles.len());
+        assert_eq!(Tile { x: 2480, y: 0 }, tiles[1]);
+        assert_eq!(Tile { x: 0, y: 3508 }, tiles[2]);
+    }
+
+    #[test]
+    fn overlap() {
+        let poster = Poster {
+            overlap: 25.4,
+            crop_marks: true,
+        };
+        let tiles = poster.tiles(4000, 3000, &PagePixels::a4());
+
+        assert_eq!(vec![Tile { x: 0, y: 0 }, Tile { x: 2180, y: 0 }], tiles);
+        assert!(poster.crop_marks(tiles[0], &PagePixels::a4()).is_empty());
+
+        let marks = poster.crop_marks(tiles[1], &PagePixels::a4());
+        assert_eq!(2, marks.len());
+        assert_eq!(300, marks[0].x);
+    }
+
+    #[test]
+    fn small_page() {
+        assert_eq!(
+            vec![Tile { x: 0, y: 0 }],
+            Poster::default().tiles(100, 100, &PagePixels::a4())
+        );
+    }
+}
@@ -19,9 +19,10 @@ mod types {
         pub fn new(string: &str) -> Self {
             let mut bytes = [0u8; 64];
             let string = string.as_bytes();
-            let len = string.len().min(64);
+            // keep the terminating zero within the 64 bytes
+            let len = string.len().min(63);
             if len > 0 {
-                string
+                string[..len]
                     .iter()
                     .enumerate()
                     .for_each(|(index, &ch)| bytes[index] = ch);
@@ -145,7 +146,6 @@ mod types {
         Roll10 = 49_u32.to_be(),
     }
 
-    #[allow(dead_code)]
     #[repr(u32)]
     pub enum Orientation {
         Portrait = 0_u32.to_be(),
@@ -216,15 +216,15 @@ struct PwgRaster(pub CString);
 // 4.3.2.2
 #[repr(C, packed)]
 struct HwResolution {
-    pub feed_res_dpi: UnsignedInteger,
     pub cross_feed_res_dpi: UnsignedInteger,
+    pub feed_res_dpi: UnsignedInteger,
 }
 
 impl HwResolution {
-    pub fn new(feed_dpi: u32, cross_feed_dpi: u32) -> Self {
+    pub fn new(cross_feed_dpi: u32, feed_dpi: u32) -> Self {
         Self {
-            feed_res_dpi: UnsignedInteger::new(feed_dpi),
             cross_feed_res_dpi: UnsignedInteger::new(cross_feed_dpi),
+            feed_res_dpi: UnsignedInteger::new(feed_dpi),
         }
     }
 }
@@ -237,27 +237,27 @@ struct PageSize {
 }
 
 impl PageSize {
-    pub fn new(page_pixels: &A4Pixels) -> Self {
-        let width_dpi = page_pixels.resolution_width as u64;
-        let width_points = ((page_pixels.width as u64) * 72 + width_dpi / 2) / width_dpi;
-
-        let height_dpi = page_pixels.resolution_height as u64;
-        let height_points = ((page_pixels.height as u64) * 72 + height_dpi / 2) / height_dpi;
+    pub fn new(page_pixels: &PagePixels) -> Self {
+        let (width_points, height_points) = page_pixels.media.points();
 
         Self {
-            width: UnsignedInteger::new(width_points.min(u32::MAX as u64) as u32),
-            height: UnsignedInteger::new(height_points.min(u32::MAX as u64) as u32),
+            width: UnsignedInteger::new(width_points.min(u32::MAX as usize) as u32),
+            height: UnsignedInteger::new(height_points.min(u32::MAX as usize) as u32),
         }
     }
 }
 
 use types::*;
 
-use crate::{error::Error, render::A4Pixels};
+use crate::{
+    color::ColorMode,
+    duplex::Sides,
+    error::Error,
+    render::{self, PagePixels, PageSetup},
+};
 
 const PWG_SYNC_WORD: &str = "RaS2";
 const PWG_RASTER: &str = "PwgRaster";
-const ISO_A4_NAME: &str = "iso_a4_210x297mm";
 
 #[repr(C, packed)]
 #[allow(non_snake_case)]
@@ -314,7 +314,7 @@ struct PageHeader {
 }
 
 impl PageHeader {
-    pub fn new(page_pixels: &A4Pixels) -> Self {
+    pub fn new(page_pixels: &PagePixels, setup: &PageSetup) -> Self {
         Self {
             PwgRaster: PwgRaster(CString::new(PWG_RASTER)),
             MediaColor: CString::default(),
@@ -322,10 +322,10 @@ impl PageHeader {
             PrintContentOptimize: CString::default(),
             Reserved1: Default::default(),
             CutMedia: When::Never,
-            Duplex: Boolean::new(false),
+            Duplex: Boolean::new(setup.sides != Sides::OneSided),
             HWResolution: HwResolution::new(
-                page_pixels.resolution_height as u32,
                 page_pixels.resolution_width as u32,
+                page_pixels.resolution_height as u32,
             ),
             Reserved2: Default::default(),
             InsertSheet: Boolean::new(false),
@@ -335,26 +335,26 @@ impl PageHeader {
             MediaPosition: MediaPosition::Auto,      // TODO
             MediaWeight: UnsignedInteger::default(), // TODO
             Reserved4: Default::default(),
-            NumCopies: UnsignedInteger::new(1_u32), // TODO
-            Orientation: Orientation::Portrait,
+            NumCopies: UnsignedInteger::new(setup.copies),
+            Orientation: orientation(setup.orientation),
             Reserved5: Default::default(),
             PageSize: PageSize::new(page_pixels),
             Reserved6: Default::default(),
-            Tumble: Boolean::new(false), // TODO?
+            Tumble: Boolean::new(setup.sides == Sides::TwoSidedShortEdge),
             Width: UnsignedInteger::new(page_pixels.width as u32),
             Height: UnsignedInteger::new(page_pixels.height as u32),
             Reserved7: Default::default(),
-            BitsPerColor: UnsignedInteger::new(page_pixels.bits_per_pixel as u32),
+            BitsPerColor: UnsignedInteger::new(page_pixels.color.bits_per_color() as u32),
             BitsPerPixel: UnsignedInteger::new(page_pixels.bits_per_pixel as u32),
             BytesPerLine: UnsignedInteger::new(page_pixels.bytes_per_line() as u32),
             ColorOrder: ColorOrder::Chunky,
-            ColorSpace: ColorSpace::Sgray,
+            ColorSpace: color_space(page_pixels.color),
             Reserved8: Default::default(),
-            NumColors: UnsignedInteger::new(1_u32),
+            NumColors: UnsignedInteger::new(page_pixels.color.num_colors() as u32),
             Reserved9: Default::default(),
-            TotalPageCount: UnsignedInteger::new(1_u32), // TODO
-            CrossFeedTransform: Integer::new(1),
-            FeedTransform: Integer::new(1),
+            TotalPageCount: UnsignedInteger::new(setup.total_pages as u32),
+            CrossFeedTransform: Integer::new(transform(setup.transform.flip_cross_feed)),
+            FeedTransform: Integer::new(transform(setup.transform.flip_feed)),
             ImageBoxLeft: UnsignedInteger::default(),
             ImageBoxTop: UnsignedInteger::default(),
             ImageBoxRight: UnsignedInteger::default(),
@@ -367,7 +367,7 @@ impl PageHeader {
             VendorData: Default::default(),
             Reserved11: Default::default(),
             RenderingIntent: CString::default(),
-            PageSizeName: CString::new(ISO_A4_NAME), // TODO
+            PageSizeName: CString::new(&page_pixels.media.name()),
         }
     }
 
@@ -376,13 +376,48 @@ impl PageHeader {
     }
 }
 
-pub(crate) fn write_file_header(_: &A4Pixels, writer: &mut impl Write) -> Result<(), Error> {
+fn color_space(color: ColorMode) -> ColorSpace {
+    match color {
+        ColorMode::Sgray8 | ColorMode::Sgray1(_) | ColorMode::Sgray16 => ColorSpace::Sgray,
+        ColorMode::Black8 | ColorMode::Black1(_) => ColorSpace::Black,
+        ColorMode::Cmyk8(_) => ColorSpace::Cmyk,
+        ColorMode::AdobeRgb8 | ColorMode::AdobeRgb16 => ColorSpace::AdobeRgb,
+        ColorMode::Rgb8 | ColorMode::Rgb16 => ColorSpace::Rgb,
+        ColorMode::Srgb8 | ColorMode::Srgb16 => ColorSpace::Srgb,
+    }
+}
+
+fn orientation(orientation: render::Orientation) -> Orientation {
+    match orientation {
+        // resolved per page before
+        render::Orientation::Portrait
+        | render::Orientation::Landscape
+        | render::Orientation::Auto => Orientation::Portrait,
+        render::Orientation::Rotated => Orientation::Landscape,
+        render::Orientation::ReversePortrait => Orientation::ReversePortrait,
+        render::Orientation::ReverseLandscape => Orientation::ReverseLandscape,
+    }
+}
+
+fn transform(flip: bool) -> i32 {
+    if flip {
+        -1
+    } else {
+        1
+    }
+}
+
+pub(crate) fn write_file_header(_: &PagePixels, writer: &mut impl Write) -> Result<(), Error> {
     writer.write_all(PWG_SYNC_WORD.as_bytes())?;
     Ok(())
 }
 
-pub(crate) fn write_page_header(pixels: &A4Pixels, writer: &mut impl Write) -> Result<(), Error> {
-    writer.write_all(PageHeader::new(pixels).as_slice())?;
+pub(crate) fn write_page_header(
+    pixels: &PagePixels,
+    setup: &PageSetup,
+    writer: &mut impl Write,
+) -> Result<(), Error> {
+    writer.write_all(PageHeader::new(pixels, setup).as_slice())?;
     Ok(())
 }
 
@@ -391,11 +426,72 @@ mod tests {
     use std::mem::size_of;
 
     use super::PageHeader;
+    use crate::{
+        duplex::{SheetBack, Sides, Transform},
+        render::{Orientation, PagePixels, PageSetup},
+    };
 
     pub const PWG_HEADER_SIZE: usize = 1796;
 
+    fn setup(sides: Sides, transform: Transform) -> PageSetup {
+        PageSetup {
+            sides,
+            transform,
+            orientation: Orientation::Portrait,
+            copies: 1,
+            total_pages: 1,
+        }
+    }
+
+    fn field(header: &PageHeader, offset: usize) -> i32 {
+        i32::from_be_bytes(header.as_slice()[offset..offset + 4].try_into().unwrap())
+    }
+
     #[test]
     fn test_page_size_matches() {
         assert_eq!(PWG_HEADER_SIZE, size_of::<PageHeader>())
     }
+
+    #[test]
+    fn copies_fields() {
+        let setup = PageSetup {
+            copies: 3,
+            total_pages: 7,
+            ..setup(Sides::OneSided, Transform::default())
+        };
+        let header = PageHeader::new(&PagePixels::a4(), &setup);
+        // NumCopies and TotalPageCount
+        assert_eq!([3, 7], [340, 452].map(|at| field(&header, at)));
+    }
+
+    #[test]
+    fn duplex_fields() {
+        let front = setup(Sides::OneSided, Transform::default());
+        let header = PageHeader::new(&PagePixels::a4(), &front);
+        // Duplex, Tumble, CrossFeedTransform and FeedTransform
+        assert_eq!(
+            [0, 0, 1, 1],
+            [272, 368, 456, 460].map(|at| field(&header, at))
+        );
+
+        let back = setup(
+            Sides::TwoSidedShortEdge,
+            Transform::new(Sides::TwoSidedShortEdge, SheetBack::Flipped, 1),
+        );
+        let header = PageHeader::new(&PagePixels::a4(), &back);
+        assert_eq!(
+            [1, 1, -1, 1],
+            [272, 368, 456, 460].map(|at| field(&header, at))
+        );
+
+        let back = setup(
+            Sides::TwoSidedLongEdge,
+            Transform::new(Sides::TwoSidedLongEdge, SheetBack::Rotated, 1),
+        );
+        let header = PageHeader::new(&PagePixels::a4(), &back);
+        assert_eq!(
+            [1, 0, -1, -1],
+            [272, 368, 456, 460].map(|at| field(&header, at))
+        );
+    }
 }
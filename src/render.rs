@@ -1,39 +1,169 @@
-use std::sync::Arc;
+#[cfg(not(feature = "static"))]
+use std::env;
+use std::path::Path;
+use std::{
+    collections::HashMap,
+    io::{self, BufWriter, Write},
+    mem,
+    sync::Arc,
+};
 
+use async_channel::{bounded, Sender};
 use blocking::unblock;
+use futures_io::AsyncWrite;
+use futures_lite::{future::zip, AsyncWriteExt, Stream};
 use pdfium_render::prelude::{
-    PdfBitmap, PdfBitmapFormat, PdfPageRenderRotation, PdfRenderConfig, Pdfium,
+    PdfBitmap, PdfBitmapFormat, PdfDocument, PdfPage, PdfPageIndex, PdfPageRenderRotation,
+    PdfPoints, PdfRenderConfig, Pdfium, PdfiumError, PdfiumInternalError, PdfiumLibraryBindings,
+    Pixels,
 };
 
-use crate::{error::Error, pwg, rle::compress, urf};
+use crate::{
+    booklet,
+    color::{ColorMode, LineConverter},
+    copies::{self, Collation, Copies},
+    duplex::{SheetBack, Sides, Transform},
+    error::Error,
+    media::MediaSize,
+    number_up::NumberUp,
+    page_range::PageRanges,
+    poster::{Poster, Tile},
+    pwg,
+    rle::{compress, compress_repeated},
+    scaling::{Area, Placement, Scaling},
+    urf,
+};
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+/// Orientation of the pages on the portrait media, IPP
+/// `orientation-requested`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum Orientation {
+    #[default]
     Portrait = 0,
+    /// Landscape pages are rotated by 90 degrees, portrait pages are not.
     Landscape = 1,
+    /// Pages are rotated by 180 degrees, IPP `reverse-portrait` (6).
+    ReversePortrait = 2,
+    /// Pages are rotated by 270 degrees, IPP `reverse-landscape` (5).
+    ReverseLandscape = 3,
+    /// Pages whose orientation differs from the media, or from the cell for
+    /// N-up, are rotated by 90 degrees.
+    Auto,
+    /// Pages are rotated by 90 degrees, IPP `landscape` (4).
+    Rotated,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
-#[repr(usize)]
-pub enum Resolution {
-    Dpi300 = 300,
-    Dpi400 = 400,
-    Dpi600 = 600,
+impl Orientation {
+    /// Orientation of a page of `width` by `height` points on the area, never
+    /// `Landscape` or `Auto`.
+    fn resolve(self, width: f32, height: f32, area: Area) -> Self {
+        match self {
+            Orientation::Landscape if width > height => Orientation::Rotated,
+            Orientation::Auto if (width > height) != (area.width > area.height) => {
+                Orientation::Rotated
+            }
+            Orientation::Landscape | Orientation::Auto => Orientation::Portrait,
+            orientation => orientation,
+        }
+    }
+
+    fn rotation(self) -> PdfPageRenderRotation {
+        match self {
+            // resolved per page before
+            Orientation::Portrait | Orientation::Landscape | Orientation::Auto => {
+                PdfPageRenderRotation::None
+            }
+            Orientation::Rotated => PdfPageRenderRotation::Degrees90,
+            Orientation::ReversePortrait => PdfPageRenderRotation::Degrees180,
+            Orientation::ReverseLandscape => PdfPageRenderRotation::Degrees270,
+        }
+    }
+
+    fn is_landscape(self) -> bool {
+        matches!(self, Orientation::Rotated | Orientation::ReverseLandscape)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Resolution(u32);
+
+#[allow(non_upper_case_globals)]
+impl Resolution {
+    pub const Dpi300: Resolution = Resolution(300);
+    pub const Dpi400: Resolution = Resolution(400);
+    pub const Dpi600: Resolution = Resolution(600);
+
+    /// Highest resolution accepted, twice that of the finest printers.
+    pub const MAX_DPI: u32 = 9600;
+
+    /// Accepts 1 to [`Resolution::MAX_DPI`] dpi.
+    pub fn new(dpi: u32) -> Result<Self, Error> {
+        if (1..=Self::MAX_DPI).contains(&dpi) {
+            Ok(Self(dpi))
+        } else {
+            Err(Error::InvalidResolution(dpi))
+        }
+    }
+
+    /// Checks the resolution against those the printer advertises, e.g. in
+    /// `pwg-raster-document-resolution-supported`.
+    pub fn validate(self, supported: &[Resolution]) -> Result<Self, Error> {
+        if supported.contains(&self) {
+            Ok(self)
+        } else {
+            Err(Error::UnsupportedResolution(self.0))
+        }
+    }
+
+    pub fn dpi(&self) -> u32 {
+        self.0
+    }
+}
+
+impl Default for Resolution {
+    fn default() -> Self {
+        Self::Dpi300
+    }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(usize)]
 pub enum Format {
     Pwg,
     Urf,
 }
 
-pub(crate) enum Colors {
-    Gray,
-    Colored,
+/// Rendering options, the defaults render portrait one-sided 8-bit gray A4 at
+/// 300 dpi.
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    pub orientation: Orientation,
+    pub media: MediaSize,
+    pub resolution_width: Resolution,
+    pub resolution_height: Resolution,
+    pub color: ColorMode,
+    pub sides: Sides,
+    pub sheet_back: SheetBack,
+    /// Ends a duplex job with an odd page count with a blank back side, so
+    /// that the next job starts on a fresh sheet.
+    pub pad_odd_pages: bool,
+    pub copies: Copies,
+    pub page_ranges: PageRanges,
+    pub scaling: Scaling,
+    pub number_up: NumberUp,
+    /// Imposes the pages as a booklet folded in the middle of the sheet,
+    /// overriding orientation, sides and number up.
+    pub booklet: bool,
+    /// Tiles each page across several sheets, overriding orientation, scaling,
+    /// number up and booklet.
+    pub poster: Option<Poster>,
+    /// User or owner password of an encrypted PDF.
+    pub password: Option<String>,
 }
 
-pub(crate) struct A4Pixels {
+pub(crate) struct PagePixels {
+    pub media: MediaSize,
+    pub color: ColorMode,
     pub width: usize,
     pub resolution_width: usize,
     pub height: usize,
@@ -41,51 +171,42 @@ pub(crate) struct A4Pixels {
     pub bits_per_pixel: usize,
 }
 
-impl A4Pixels {
+impl PagePixels {
     pub(crate) fn new(
+        media: MediaSize,
         orientation: Orientation,
         resolution_width: Resolution,
         resolution_height: Resolution,
-        colors: Colors,
+        color: ColorMode,
     ) -> Self {
-        let portrait_width = match resolution_width {
-            Resolution::Dpi300 => 2480,
-            Resolution::Dpi400 => 3307,
-            Resolution::Dpi600 => 4960,
-        };
-
-        let portrait_height = match resolution_height {
-            Resolution::Dpi300 => 3508,
-            Resolution::Dpi400 => 4667,
-            Resolution::Dpi600 => 7016,
-        };
+        let (portrait_width, portrait_height) = media.pixels(
+            resolution_width.dpi() as usize,
+            resolution_height.dpi() as usize,
+        );
 
-        let (width, height) = match orientation {
-            Orientation::Portrait => (portrait_width, portrait_height),
-            Orientation::Landscape => (portrait_height, portrait_width),
+        let (width, height) = match orientation.is_landscape() {
+            false => (portrait_width, portrait_height),
+            true => (portrait_height, portrait_width),
         };
 
-        let (resolution_width, resolution_height) = match orientation {
-            Orientation::Portrait => (resolution_width, resolution_height),
-            Orientation::Landscape => (resolution_height, resolution_width),
-        };
-
-        let bits_per_pixel = match colors {
-            Colors::Gray => 8,
-            Colors::Colored => 24,
+        let (resolution_width, resolution_height) = match orientation.is_landscape() {
+            false => (resolution_width, resolution_height),
+            true => (resolution_height, resolution_width),
         };
 
         Self {
+            media,
+            color,
             width,
-            resolution_width: resolution_width as usize,
+            resolution_width: resolution_width.dpi() as usize,
             height,
-            resolution_height: resolution_height as usize,
-            bits_per_pixel,
+            resolution_height: resolution_height.dpi() as usize,
+            bits_per_pixel: color.bits_per_pixel(),
         }
     }
 
     pub fn bytes_per_line(&self) -> usize {
-        (self.width * self.bits_per_pixel + 7) / 8
+        (self.width * self.bits_per_pixel).div_ceil(8)
     }
 
     pub fn len(&self) -> usize {
@@ -93,102 +214,1191 @@ impl A4Pixels {
     }
 }
 
-pub async fn render(
+#[cfg(test)]
+impl PagePixels {
+    /// Portrait 8-bit gray A4 at 300 dpi, shared by the tests.
+    pub(crate) fn a4() -> Self {
+        Self::new(
+            MediaSize::IsoA4,
+            Orientation::Portrait,
+            Resolution::Dpi300,
+            Resolution::Dpi300,
+            ColorMode::Sgray8,
+        )
+    }
+}
+
+/// Content of a sheet side, i.e. an output page.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum SheetSide {
+    /// Pages in the N-up cells, `None` leaving a cell blank.
+    Pages(Vec<Option<usize>>),
+    /// Part of a poster page.
+    Tile(usize, Tile),
+}
+
+/// Values of a single page header.
+pub(crate) struct PageSetup {
+    pub sides: Sides,
+    pub transform: Transform,
+    pub orientation: Orientation,
+    pub copies: u32,
+    pub total_pages: usize,
+}
+
+/// Renders with a pdfium binding of its own; use a [`Renderer`] to bind once
+/// for many jobs.
+pub async fn render(pdf: Arc<Vec<u8>>, format: Format, options: Options) -> Result<Vec<u8>, Error> {
+    validate(format, &options)?;
+    unblock(Renderer::new)
+        .await?
+        .render(pdf, format, options)
+        .await
+}
+
+/// Renders into a blocking writer, e.g. a file or socket, as each page is
+/// produced and returns the writer.
+pub async fn render_to_writer<W>(
     pdf: Arc<Vec<u8>>,
     format: Format,
-    orientation: Orientation,
-    resolution_width: Resolution,
-    resolution_height: Resolution,
-) -> Result<Vec<u8>, Error> {
+    options: Options,
+    writer: W,
+) -> Result<W, Error>
+where
+    W: Write + Send + 'static,
+{
+    validate(format, &options)?;
+    unblock(Renderer::new)
+        .await?
+        .render_to_writer(pdf, format, options, writer)
+        .await
+}
+
+/// Renders into an async writer, e.g. an IPP request body, as each page is
+/// produced.
+pub async fn render_to_async_writer<W>(
+    pdf: Arc<Vec<u8>>,
+    format: Format,
+    options: Options,
+    writer: &mut W,
+) -> Result<(), Error>
+where
+    W: AsyncWrite + Unpin,
+{
+    validate(format, &options)?;
+    unblock(Renderer::new)
+        .await?
+        .render_to_async_writer(pdf, format, options, writer)
+        .await
+}
+
+/// Renders as a stream of encoded chunks, the file header and then one chunk
+/// per page, produced as the stream is consumed. A failure ends the stream
+/// with the error; dropping the stream stops the rendering.
+pub fn render_stream(
+    pdf: Arc<Vec<u8>>,
+    format: Format,
+    options: Options,
+) -> impl Stream<Item = Result<Vec<u8>, Error>> + Send + Unpin {
+    spawn_stream(
+        || Renderer::new().map(|renderer| renderer.pdfium),
+        pdf,
+        format,
+        options,
+    )
+}
+
+/// Environment variable naming the pdfium library or its directory, ignored
+/// under the `static` feature.
+pub const PDFIUM_LIBRARY_VAR: &str = "PDF2PWG_PDFIUM_LIBRARY";
+
+/// Renderer binding pdfium once for all its jobs. Clones share the binding,
+/// which serializes the calls into pdfium across the blocking thread pool.
+#[derive(Clone, Debug)]
+pub struct Renderer {
+    pdfium: Arc<Pdfium>,
+}
+
+impl Renderer {
+    /// Binds pdfium, which loads the library; call it off the async executor,
+    /// e.g. in `blocking::unblock`. The library is the one named by the
+    /// `PDF2PWG_PDFIUM_LIBRARY` environment variable, or else the one in the
+    /// working directory, or else the system one.
+    #[cfg(not(feature = "static"))]
+    pub fn new() -> Result<Self, Error> {
+        match env::var_os(PDFIUM_LIBRARY_VAR) {
+            Some(path) => Self::with_library(path),
+            None => Ok(Self::from_bindings(
+                Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path("./"))
+                    .or_else(|_| Pdfium::bind_to_system_library())?,
+            )),
+        }
+    }
+
+    /// Binds the pdfium linked into the executable by the `static` feature.
+    #[cfg(feature = "static")]
+    pub fn new() -> Result<Self, Error> {
+        Ok(Self::from_bindings(
+            Pdfium::bind_to_statically_linked_library()?,
+        ))
+    }
+
+    /// Binds the pdfium library at the path, either the library itself or the
+    /// directory containing it.
+    #[cfg(not(feature = "static"))]
+    pub fn with_library(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let library = match path.is_dir() {
+            true => Pdfium::pdfium_platform_library_name_at_path(path),
+            false => path.to_path_buf(),
+        };
+        Ok(Self::from_bindings(Pdfium::bind_to_library(library)?))
+    }
+
+    /// Fails with `Error::StaticallyLinked`, as the `static` feature links
+    /// pdfium into the executable.
+    #[cfg(feature = "static")]
+    pub fn with_library(_path: impl AsRef<Path>) -> Result<Self, Error> {
+        Err(Error::StaticallyLinked)
+    }
+
+    fn from_bindings(bindings: Box<dyn PdfiumLibraryBindings>) -> Self {
+        Self {
+            pdfium: Arc::new(Pdfium::new(bindings)),
+        }
+    }
+
+    pub async fn render(
+        &self,
+        pdf: Arc<Vec<u8>>,
+        format: Format,
+        options: Options,
+    ) -> Result<Vec<u8>, Error> {
+        let pdfium = self.pdfium.clone();
+        unblock(move || {
+            let mut output = Vec::new();
+            do_render(&pdfium, &pdf, format, &options, &mut output)?;
+            Ok(output)
+        })
+        .await
+    }
+
+    /// See [`render_to_writer`].
+    pub async fn render_to_writer<W>(
+        &self,
+        pdf: Arc<Vec<u8>>,
+        format: Format,
+        options: Options,
+        writer: W,
+    ) -> Result<W, Error>
+    where
+        W: Write + Send + 'static,
+    {
+        let pdfium = self.pdfium.clone();
+        unblock(move || {
+            let mut writer = BufWriter::new(writer);
+            do_render(&pdfium, &pdf, format, &options, &mut writer)?;
+            writer
+                .into_inner()
+                .map_err(|error| Error::Compose(error.into_error()))
+        })
+        .await
+    }
+
+    /// See [`render_to_async_writer`].
+    pub async fn render_to_async_writer<W>(
+        &self,
+        pdf: Arc<Vec<u8>>,
+        format: Format,
+        options: Options,
+        writer: &mut W,
+    ) -> Result<(), Error>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let pdfium = self.pdfium.clone();
+        let (sender, receiver) = bounded(1);
+        let rendering = unblock(move || {
+            do_render(
+                &pdfium,
+                &pdf,
+                format,
+                &options,
+                &mut ChunkWriter::new(sender),
+            )
+        });
+
+        let writing = async move {
+            while let Ok(Ok(chunk)) = receiver.recv().await {
+                writer.write_all(&chunk).await?;
+            }
+            writer.flush().await
+        };
+
+        // a failed write drops the receiver, which stops the rendering
+        let (rendered, written) = zip(rendering, writing).await;
+        written?;
+        rendered
+    }
+
+    /// See [`render_stream`].
+    pub fn render_stream(
+        &self,
+        pdf: Arc<Vec<u8>>,
+        format: Format,
+        options: Options,
+    ) -> impl Stream<Item = Result<Vec<u8>, Error>> + Send + Unpin {
+        let pdfium = self.pdfium.clone();
+        spawn_stream(move || Ok(pdfium), pdf, format, options)
+    }
+}
+
+fn spawn_stream(
+    pdfium: impl FnOnce() -> Result<Arc<Pdfium>, Error> + Send + 'static,
+    pdf: Arc<Vec<u8>>,
+    format: Format,
+    options: Options,
+) -> impl Stream<Item = Result<Vec<u8>, Error>> + Send + Unpin {
+    let (sender, receiver) = bounded(1);
     unblock(move || {
-        do_render(
-            &pdf,
-            format,
-            orientation,
-            resolution_width,
-            resolution_height,
-        )
+        let rendered = validate(format, &options)
+            .and_then(|_| pdfium())
+            .and_then(|pdfium| {
+                do_render(
+                    &pdfium,
+                    &pdf,
+                    format,
+                    &options,
+                    &mut ChunkWriter::new(sender.clone()),
+                )
+            });
+        if let Err(error) = rendered {
+            // nobody listens once the stream is dropped
+            let _ = sender.send_blocking(Err(error));
+        }
     })
-    .await
+    .detach();
+
+    Box::pin(receiver)
+}
+
+/// Buffers the output and sends it on flush, i.e. after the file header and
+/// after each page.
+struct ChunkWriter {
+    sender: Sender<Result<Vec<u8>, Error>>,
+    chunk: Vec<u8>,
+}
+
+impl ChunkWriter {
+    fn new(sender: Sender<Result<Vec<u8>, Error>>) -> Self {
+        Self {
+            sender,
+            chunk: Vec::new(),
+        }
+    }
+}
+
+impl Write for ChunkWriter {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.chunk.extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.chunk.is_empty() {
+            return Ok(());
+        }
+        self.sender
+            .send_blocking(Ok(mem::take(&mut self.chunk)))
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))
+    }
+}
+
+/// Rejects options the format cannot encode, before pdfium is bound.
+/// Largest BGR sheet or raster of a page in bytes, just under 4 GiB, enough
+/// for A0 at 600 dpi in 16-bit sRGB.
+const MAX_PAGE_LEN: usize = u32::MAX as usize;
+
+fn validate(format: Format, options: &Options) -> Result<(), Error> {
+    if format == Format::Urf && !urf::supports(options.color) {
+        return Err(Error::UnsupportedColorMode(options.color));
+    }
+    // URF has a single resolution for both directions
+    if format == Format::Urf && options.resolution_width != options.resolution_height {
+        return Err(Error::UnequalResolutions(
+            options.resolution_width.dpi(),
+            options.resolution_height.dpi(),
+        ));
+    }
+
+    // media narrower than a pixel at the resolution leaves nothing to raster
+    let page = PagePixels::new(
+        options.media,
+        Orientation::Portrait,
+        options.resolution_width,
+        options.resolution_height,
+        options.color,
+    );
+    if page.width == 0 || page.height == 0 {
+        return Err(Error::InvalidMediaSize);
+    }
+
+    // headers hold the size and line length in 32 bits, and the BGR sheet and
+    // the raster are allocated in one piece each
+    let line = page
+        .width
+        .checked_mul(page.bits_per_pixel.max(24))
+        .map(|bits| bits.div_ceil(8));
+    let len = line.and_then(|line| line.checked_mul(page.height));
+    if u32::try_from(page.width).is_err()
+        || u32::try_from(page.height).is_err()
+        || line.is_none_or(|line| u32::try_from(line).is_err())
+        || len.is_none_or(|len| len > MAX_PAGE_LEN.min(isize::MAX as usize))
+    {
+        return Err(Error::InvalidMediaSize);
+    }
+    Ok(())
 }
 
 fn do_render(
+    pdfium: &Pdfium,
     pdf: &[u8],
     format: Format,
-    orientation: Orientation,
-    resolution_width: Resolution,
-    resolution_height: Resolution,
-) -> Result<Vec<u8>, Error> {
-    let pdfium = Pdfium::new(
-        Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path("./"))
-            .or_else(|_| Pdfium::bind_to_system_library())?,
-    );
-    let document = pdfium.load_pdf_from_byte_slice(&pdf, None)?;
+    options: &Options,
+    output: &mut impl Write,
+) -> Result<(), Error> {
+    validate(format, options)?;
+    let color = options.color;
+
+    let booklet_options = booklet_options_for(options)?;
+    let options = booklet_options.as_ref().unwrap_or(options);
 
-    let color_a4 = A4Pixels::new(
+    let password = options.password.as_deref();
+    let document = pdfium
+        .load_pdf_from_byte_slice(pdf, password)
+        .map_err(|error| load_error(error, password))?;
+
+    let page = PagePixels::new(
+        options.media,
         Orientation::Portrait,
-        resolution_width,
-        resolution_height,
-        Colors::Colored,
+        options.resolution_width,
+        options.resolution_height,
+        color,
     );
 
-    let render_config = PdfRenderConfig::new()
-        .scale_page_width_by_factor(color_a4.resolution_width as f32 / 72.0)
-        .use_grayscale_rendering(true)
-        .set_text_smoothing(false)
-        .use_print_quality(true);
+    let area = Area {
+        x: 0,
+        y: 0,
+        width: page.width,
+        height: page.height,
+    };
+    let mut sheet = vec![0xffu8; page.width * 3 * page.height];
+    let mut raster_bytes = vec![0u8; page.len()];
+    let mut converter = LineConverter::new(color, page.width);
+    let mut mirrored_line = vec![0u8; page.width * 3];
+
+    let copies = output_copies(format, options)?;
+    // a cell to resolve the orientation of the first page on a side against,
+    // all cells being the same size
+    let cell = options.number_up.cells(&page, Orientation::Portrait)[0];
+    let sequence = sheet_sequence(
+        options,
+        copies,
+        &page,
+        document.pages().len() as usize,
+        |pdf_index| {
+            let pdf_page = document.pages().get(pdf_index as PdfPageIndex)?;
+            Ok((pdf_page.width().value, pdf_page.height().value))
+        },
+    )?;
+    let total_pages = sequence.len();
+
+    match format {
+        Format::Pwg => pwg::write_file_header(&page, output)?,
+        Format::Urf => urf::write_file_header(&page, total_pages as u32, output)?,
+    }
+    output.flush()?;
+
+    let white_line = vec![0xffu8; page.width * 3];
+    let mut blank_line = vec![0u8; page.bytes_per_line()];
+    converter.convert(&white_line, &mut blank_line);
+
+    // copies repeat sheet sides, which are rendered and encoded once and kept
+    // until their last repeat
+    let mut repeats = HashMap::<SheetSide, usize>::new();
+    for sheet_side in sequence.iter().flatten() {
+        *repeats.entry(sheet_side.clone()).or_default() += 1;
+    }
+    let mut encoded_sides = HashMap::<SheetSide, EncodedSide>::new();
+
+    for (page_index, sheet_side) in sequence.into_iter().enumerate() {
+        let transform = Transform::new(options.sides, options.sheet_back, page_index);
+        let mut setup = PageSetup {
+            sides: options.sides,
+            transform,
+            orientation: page_orientation(options).resolve(0.0, 0.0, area),
+            copies: copies.printer_count(),
+            total_pages,
+        };
+
+        let Some(sheet_side) = sheet_side else {
+            write_page_header(format, &page, &setup, output)?;
+            compress_repeated(&blank_line, page.height, page.bits_per_pixel, output)?;
+            output.flush()?;
+            continue;
+        };
+
+        // a repeat on the other side of a sheet is flipped differently
+        let encoded = match encoded_sides
+            .remove(&sheet_side)
+            .filter(|encoded| encoded.transform == transform)
+        {
+            Some(encoded) => encoded,
+            None => {
+                let orientation =
+                    render_sheet_side(&document, options, &page, cell, &sheet_side, &mut sheet)?;
+                let mut raster = Vec::new();
+                converter.start_page();
+                let stride = page.width * 3;
+                for (y, raster_line) in raster_bytes.chunks_mut(page.bytes_per_line()).enumerate() {
+                    let y = match transform.flip_feed {
+                        true => page.height - 1 - y,
+                        false => y,
+                    };
+                    let bgr_line = &sheet[y * stride..][..stride];
 
-    let render_config = match orientation {
-        Orientation::Portrait => render_config,
-        Orientation::Landscape => {
-            render_config.rotate_if_landscape(PdfPageRenderRotation::Degrees90, true)
+                    if transform.flip_cross_feed {
+                        mirrored_line
+                            .chunks_exact_mut(3)
+                            .zip(bgr_line.chunks_exact(3).rev())
+                            .for_each(|(mirrored, pixel)| mirrored.copy_from_slice(pixel));
+                        converter.convert(&mirrored_line, raster_line);
+                    } else {
+                        converter.convert(bgr_line, raster_line);
+                    }
+                }
+                compress(&raster_bytes, page.width, page.bits_per_pixel, &mut raster)?;
+
+                EncodedSide {
+                    transform,
+                    orientation,
+                    raster,
+                }
+            }
+        };
+
+        if let Some(orientation) = encoded.orientation {
+            setup.orientation = orientation;
+        }
+        write_page_header(format, &page, &setup, output)?;
+        output.write_all(&encoded.raster)?;
+        output.flush()?;
+
+        if let Some(repeats) = repeats.get_mut(&sheet_side) {
+            *repeats -= 1;
+            if *repeats > 0 {
+                encoded_sides.insert(sheet_side, encoded);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Compressed raster of a sheet side kept for the copies repeating it.
+struct EncodedSide {
+    transform: Transform,
+    orientation: Option<Orientation>,
+    raster: Vec<u8>,
+}
+
+/// Renders the pages or the poster tile of a sheet side onto the BGR sheet and
+/// returns the orientation of the first page, which the header takes.
+fn render_sheet_side(
+    document: &PdfDocument,
+    options: &Options,
+    page: &PagePixels,
+    cell: Area,
+    sheet_side: &SheetSide,
+    sheet: &mut [u8],
+) -> Result<Option<Orientation>, Error> {
+    sheet.fill(0xff);
+    match sheet_side {
+        SheetSide::Pages(pdf_indices) => {
+            let pdf_pages = pdf_indices
+                .iter()
+                .map(|pdf_index| {
+                    pdf_index
+                        .map(|pdf_index| document.pages().get(pdf_index as PdfPageIndex))
+                        .transpose()
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            // the direction applies to the pages as read, i.e. turned like the
+            // first page
+            let first = pdf_pages.iter().flatten().next().map(|pdf_page| {
+                page_orientation(options).resolve(
+                    pdf_page.width().value,
+                    pdf_page.height().value,
+                    cell,
+                )
+            });
+            let cells = options
+                .number_up
+                .cells(page, first.unwrap_or(Orientation::Portrait));
+
+            let mut orientation = None;
+            for (&cell, pdf_page) in cells.iter().zip(&pdf_pages) {
+                let Some(pdf_page) = pdf_page else {
+                    continue;
+                };
+                let page_orientation = render_page(pdf_page, options, page, cell, sheet)?;
+                orientation.get_or_insert(page_orientation);
+            }
+            Ok(orientation)
+        }
+        SheetSide::Tile(pdf_index, tile) => {
+            let pdf_page = document.pages().get(*pdf_index as PdfPageIndex)?;
+            render_tile(&pdf_page, options, page, *tile, sheet)?;
+            Ok(Some(Orientation::Portrait))
+        }
+    }
+}
+
+/// Copies as produced in the output; URF has no copies field.
+fn output_copies(format: Format, options: &Options) -> Result<Copies, Error> {
+    match (format, options.copies.collation()) {
+        (Format::Urf, Collation::Printer) => {
+            Copies::new(options.copies.count(), Collation::Collated)
+        }
+        _ => Ok(options.copies),
+    }
+}
+
+/// Sheet sides of the output in order, `None` being a blank side, given the
+/// number of pdf pages and the size of each in points.
+fn sheet_sequence(
+    options: &Options,
+    copies: Copies,
+    page: &PagePixels,
+    page_count: usize,
+    mut page_size: impl FnMut(usize) -> Result<(f32, f32), Error>,
+) -> Result<Vec<Option<SheetSide>>, Error> {
+    let pages = options.page_ranges.pages(page_count)?;
+    let sheet_sides = match (options.poster, options.booklet) {
+        (Some(poster), _) => {
+            let mut sheet_sides = Vec::new();
+            for &pdf_index in &pages {
+                let (width, height) = page_size(pdf_index)?;
+                let (width, height) = (
+                    width * page.resolution_width as f32 / 72.0,
+                    height * page.resolution_height as f32 / 72.0,
+                );
+                sheet_sides.extend(
+                    poster
+                        .tiles(width.round() as usize, height.round() as usize, page)
+                        .into_iter()
+                        .map(|tile| SheetSide::Tile(pdf_index, tile)),
+                );
+            }
+            sheet_sides
         }
+        (None, true) => booklet::sheet_sides(&pages)
+            .into_iter()
+            .map(SheetSide::Pages)
+            .collect(),
+        (None, false) => pages
+            .chunks(options.number_up.count())
+            .map(|pages| SheetSide::Pages(pages.iter().copied().map(Some).collect()))
+            .collect(),
     };
+    Ok(copies::sequence(
+        &sheet_sides,
+        copies,
+        options.sides,
+        options.pad_odd_pages,
+    ))
+}
 
-    let mut color_bitmap = PdfBitmap::empty(
-        color_a4.width as i32,
-        color_a4.height as i32,
-        PdfBitmapFormat::BGR,
-        pdfium.bindings(),
-    )?;
+/// Tells a missing or wrong password apart from other failures to load a PDF.
+fn load_error(error: PdfiumError, password: Option<&str>) -> Error {
+    match error {
+        PdfiumError::PdfiumLibraryInternalError(PdfiumInternalError::PasswordError) => {
+            match password {
+                Some(_) => Error::WrongPassword,
+                None => Error::PasswordRequired,
+            }
+        }
+        error => Error::Render(error),
+    }
+}
 
-    let gray_a4 = A4Pixels::new(
-        Orientation::Portrait,
-        resolution_width,
-        resolution_height,
-        Colors::Gray,
+/// Orientation of the pages in their cells. The cells of 2 and 6 up are
+/// landscape on portrait media, so pages are rotated to fit each cell whatever
+/// the requested orientation.
+fn page_orientation(options: &Options) -> Orientation {
+    match options.number_up.count() {
+        2 | 6 => Orientation::Auto,
+        _ => options.orientation,
+    }
+}
+
+/// Booklets are 2-up short-edge duplex with pages rotated into the halves of
+/// the sheet, so that the pages read left to right once folded. `None` unless
+/// a booklet is requested and not overridden by a poster.
+fn booklet_options_for(options: &Options) -> Result<Option<Options>, Error> {
+    if !options.booklet || options.poster.is_some() {
+        return Ok(None);
+    }
+
+    let mut number_up = NumberUp::new(2)?;
+    number_up.border = options.number_up.border;
+    number_up.gutter = options.number_up.gutter;
+
+    Ok(Some(Options {
+        orientation: Orientation::Auto,
+        sides: Sides::TwoSidedShortEdge,
+        number_up,
+        ..options.clone()
+    }))
+}
+
+/// Renders a page scaled and centred onto the area of the BGR sheet and
+/// returns its orientation.
+fn render_page(
+    pdf_page: &PdfPage,
+    options: &Options,
+    page: &PagePixels,
+    area: Area,
+    sheet: &mut [u8],
+) -> Result<Orientation, Error> {
+    // pdfium reports page sizes with /Rotate applied
+    let orientation =
+        page_orientation(options).resolve(pdf_page.width().value, pdf_page.height().value, area);
+    let (width, height) = match orientation.is_landscape() {
+        false => (pdf_page.width(), pdf_page.height()),
+        true => (pdf_page.height(), pdf_page.width()),
+    };
+
+    let (scale_width, scale_height) = (
+        page.resolution_width as f32 / 72.0,
+        page.resolution_height as f32 / 72.0,
     );
-    let mut gray_bytes = vec![0u8; gray_a4.len()];
+    let (width, height) = (width.value * scale_width, height.value * scale_height);
+    let placement = options.scaling.place(width, height, area);
+    let size = (
+        (width * placement.scale).round() as usize,
+        (height * placement.scale).round() as usize,
+    );
+    let Some(visible) = placement.visible(size.0, size.1, area) else {
+        return Ok(orientation);
+    };
 
-    let page_count = document.pages().len() as usize;
-    let mut output = Vec::with_capacity(page_count * gray_a4.len() / 50);
+    let (render_config, placement) = match (visible.width, visible.height) == size {
+        true => (
+            render_config(options.color)
+                .rotate(orientation.rotation(), true)
+                .scale_page_width_by_factor(placement.scale * scale_width)
+                .scale_page_height_by_factor(placement.scale * scale_height),
+            placement,
+        ),
+        // render only the visible part of a filled or actual size page, which
+        // takes a matrix and so drops form data
+        false => (
+            clipped_render_config(
+                render_config(options.color),
+                pdf_page,
+                orientation,
+                (
+                    placement.scale * scale_width,
+                    placement.scale * scale_height,
+                ),
+                placement,
+                visible,
+            )?,
+            Placement {
+                scale: placement.scale,
+                x: visible.x as isize,
+                y: visible.y as isize,
+            },
+        ),
+    };
+    let bitmap = pdf_page.render_with_config(&render_config)?;
 
-    match format {
-        Format::Pwg => pwg::write_file_header(&gray_a4, &mut output)?,
-        Format::Urf => urf::write_file_header(&gray_a4, page_count as u32, &mut output)?,
+    if let Some(drawn) = draw(&bitmap, placement, area, sheet, page.width) {
+        if options.number_up.border {
+            // a quarter of a millimetre, at least one pixel
+            let thickness = (page.resolution_width / 100).max(1);
+            draw_border(drawn, thickness, sheet, page.width);
+        }
     }
 
-    for pdf_page in document.pages().iter() {
-        match format {
-            Format::Pwg => pwg::write_page_header(&gray_a4, &mut output)?,
-            Format::Urf => urf::write_page_header(&gray_a4, &mut output)?,
-        }
+    Ok(orientation)
+}
 
-        pdf_page.render_into_bitmap_with_config(&mut color_bitmap, &render_config)?;
+/// Renders the `visible` part of a page at `placement` into a bitmap of its
+/// size. The matrix rotates the page in points with the y axis down, then
+/// scales it to pixels and moves the visible part to the origin.
+fn clipped_render_config(
+    render_config: PdfRenderConfig,
+    pdf_page: &PdfPage,
+    orientation: Orientation,
+    (scale_width, scale_height): (f32, f32),
+    placement: Placement,
+    visible: Area,
+) -> Result<PdfRenderConfig, Error> {
+    let (width, height) = (pdf_page.width().value, pdf_page.height().value);
+    // clockwise, like the rotation of pdfium's own rendering
+    let (a, b, c, d, e, f) = match orientation.rotation() {
+        PdfPageRenderRotation::None => (1.0, 0.0, 0.0, 1.0, 0.0, 0.0),
+        PdfPageRenderRotation::Degrees90 => (0.0, 1.0, -1.0, 0.0, height, 0.0),
+        PdfPageRenderRotation::Degrees180 => (-1.0, 0.0, 0.0, -1.0, width, height),
+        PdfPageRenderRotation::Degrees270 => (0.0, -1.0, 1.0, 0.0, 0.0, width),
+    };
 
-        color_bitmap
-            .as_raw_bytes()
-            .chunks(3)
-            .enumerate()
-            .for_each(|(index, pixel)| gray_bytes[index] = pixel[0]);
+    Ok(render_config
+        .set_fixed_size(visible.width as Pixels, visible.height as Pixels)
+        .transform(a, b, c, d, e, f)?
+        .scale(scale_width, scale_height)?
+        // in pixels, being after the scaling
+        .translate(
+            PdfPoints::new((placement.x - visible.x as isize) as f32),
+            PdfPoints::new((placement.y - visible.y as isize) as f32),
+        )?)
+}
 
-        compress(
-            &gray_bytes,
-            gray_a4.width,
-            gray_a4.bits_per_pixel,
-            &mut output,
+/// Renders a poster tile at actual size onto the whole BGR sheet. Poster pages
+/// are not rotated.
+fn render_tile(
+    pdf_page: &PdfPage,
+    options: &Options,
+    page: &PagePixels,
+    tile: Tile,
+    sheet: &mut [u8],
+) -> Result<(), Error> {
+    let (scale_width, scale_height) = (
+        page.resolution_width as f32 / 72.0,
+        page.resolution_height as f32 / 72.0,
+    );
+    // the translation applies to the page in points before scaling
+    let render_config = render_config(options.color)
+        .set_fixed_size(page.width as Pixels, page.height as Pixels)
+        .scale_page_width_by_factor(scale_width)
+        .scale_page_height_by_factor(scale_height)
+        .translate(
+            PdfPoints::new(-(tile.x as f32) / scale_width),
+            PdfPoints::new(-(tile.y as f32) / scale_height),
         )?;
+    let bitmap = pdf_page.render_with_config(&render_config)?;
+
+    let area = Area {
+        x: 0,
+        y: 0,
+        width: page.width,
+        height: page.height,
+    };
+    let placement = Placement {
+        scale: 1.0,
+        x: 0,
+        y: 0,
+    };
+    draw(&bitmap, placement, area, sheet, page.width);
+
+    if let Some(poster) = options.poster {
+        for mark in poster.crop_marks(tile, page) {
+            fill_black(mark, sheet, page.width);
+        }
+    }
+
+    Ok(())
+}
+
+fn render_config(color: ColorMode) -> PdfRenderConfig {
+    PdfRenderConfig::new()
+        .set_format(PdfBitmapFormat::BGR)
+        .use_grayscale_rendering(color.is_gray())
+        .set_text_smoothing(false)
+        .use_print_quality(true)
+}
+
+/// Copies a rendered page onto the BGR sheet, cropped to the area, and
+/// returns the area it covers.
+fn draw(
+    bitmap: &PdfBitmap,
+    placement: Placement,
+    area: Area,
+    sheet: &mut [u8],
+    sheet_width: usize,
+) -> Option<Area> {
+    let height = bitmap.height() as usize;
+    let visible = placement.visible(bitmap.width() as usize, height, area)?;
+
+    // pdfium pads bitmap rows to 4 bytes, so walk the rows by stride
+    let bytes = bitmap.as_raw_bytes();
+    let stride = bytes.len() / height;
+    let first = (visible.x as isize - placement.x) as usize;
+    for y in visible.y..visible.y + visible.height {
+        let row = &bytes[(y as isize - placement.y) as usize * stride..][first * 3..]
+            [..visible.width * 3];
+        sheet[(y * sheet_width + visible.x) * 3..][..row.len()].copy_from_slice(row);
+    }
+
+    Some(visible)
+}
+
+/// Draws a black frame just inside the area of the BGR sheet.
+fn draw_border(area: Area, thickness: usize, sheet: &mut [u8], sheet_width: usize) {
+    let thickness = thickness.min(area.width / 2).min(area.height / 2).max(1);
+    for y in area.y..area.y + area.height {
+        let row = &mut sheet[(y * sheet_width + area.x) * 3..][..area.width * 3];
+        if y < area.y + thickness || y >= area.y + area.height - thickness {
+            row.fill(0);
+        } else {
+            row[..thickness * 3].fill(0);
+            row[(area.width - thickness) * 3..].fill(0);
+        }
+    }
+}
+
+fn fill_black(area: Area, sheet: &mut [u8], sheet_width: usize) {
+    for y in area.y..area.y + area.height {
+        sheet[(y * sheet_width + area.x) * 3..][..area.width * 3].fill(0);
+    }
+}
+
+fn write_page_header(
+    format: Format,
+    page: &PagePixels,
+    setup: &PageSetup,
+    output: &mut impl Write,
+) -> Result<(), Error> {
+    match format {
+        Format::Pwg => pwg::write_page_header(page, setup, output),
+        Format::Urf => urf::write_page_header(page, setup, output),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::MediaUnit;
+
+    const A4: Area = Area {
+        x: 0,
+        y: 0,
+        width: 2480,
+        height: 3508,
+    };
+
+    /// Sheet sides of a job of A4 portrait pages.
+    fn sequence(
+        format: Format,
+        options: &Options,
+        page_count: usize,
+    ) -> Result<Vec<Option<SheetSide>>, Error> {
+        let page = PagePixels::new(
+            options.media,
+            Orientation::Portrait,
+            options.resolution_width,
+            options.resolution_height,
+            options.color,
+        );
+        let copies = output_copies(format, options)?;
+        sheet_sequence(options, copies, &page, page_count, |_| Ok((595.0, 842.0)))
+    }
+
+    #[test]
+    fn padded_sequence() {
+        let options = Options {
+            sides: Sides::TwoSidedLongEdge,
+            pad_odd_pages: true,
+            ..Default::default()
+        };
+        let sides = sequence(Format::Pwg, &options, 5).unwrap();
+        assert_eq!(6, sides.len());
+        assert_eq!(Some(SheetSide::Pages(vec![Some(4)])), sides[4]);
+        assert_eq!(None, sides[5]);
+
+        // URF repeats the document for each copy, each padded
+        let options = Options {
+            copies: Copies::new(2, Collation::Printer).unwrap(),
+            ..options
+        };
+        assert_eq!(6, sequence(Format::Pwg, &options, 5).unwrap().len());
+        let sides = sequence(Format::Urf, &options, 5).unwrap();
+        assert_eq!(12, sides.len());
+        assert_eq!(Some(SheetSide::Pages(vec![Some(0)])), sides[6]);
+        assert_eq!(None, sides[11]);
+    }
+
+    #[test]
+    fn uncollated_sequence() {
+        let options = Options {
+            sides: Sides::TwoSidedLongEdge,
+            copies: Copies::new(2, Collation::Uncollated).unwrap(),
+            ..Default::default()
+        };
+        let sides = sequence(Format::Pwg, &options, 3).unwrap();
+        let pages = sides
+            .iter()
+            .map(|side| match side {
+                Some(SheetSide::Pages(pages)) => pages[0],
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                Some(0),
+                Some(1),
+                Some(0),
+                Some(1),
+                Some(2),
+                None,
+                Some(2),
+                None
+            ],
+            pages
+        );
+        assert_eq!(
+            1,
+            output_copies(Format::Pwg, &options)
+                .unwrap()
+                .printer_count()
+        );
+    }
+
+    #[test]
+    fn booklet_sequence() {
+        let options = booklet_options_for(&Options {
+            booklet: true,
+            ..Default::default()
+        })
+        .unwrap()
+        .unwrap();
+        // 5 pages are padded to 8 on 2 sheets
+        let sides = sequence(Format::Pwg, &options, 5).unwrap();
+        assert_eq!(4, sides.len());
+        assert_eq!(Some(SheetSide::Pages(vec![None, Some(0)])), sides[0]);
+        assert_eq!(Some(SheetSide::Pages(vec![Some(3), Some(4)])), sides[3]);
+    }
+
+    #[test]
+    fn poster_sequence() {
+        let options = Options {
+            media: MediaSize::IsoA5,
+            poster: Some(Poster::default()),
+            ..Default::default()
+        };
+        // each A4 page takes 4 A5 sheets
+        let sides = sequence(Format::Pwg, &options, 2).unwrap();
+        assert_eq!(8, sides.len());
+        assert_eq!(
+            Some(SheetSide::Tile(1, Tile { x: 1748, y: 2480 })),
+            sides[7]
+        );
+    }
+
+    #[test]
+    fn poster_overrides_booklet() {
+        let options = Options {
+            media: MediaSize::IsoA5,
+            booklet: true,
+            poster: Some(Poster::default()),
+            ..Default::default()
+        };
+        // the tiles stay one-sided
+        assert!(booklet_options_for(&options).unwrap().is_none());
+        let sides = sequence(Format::Pwg, &options, 2).unwrap();
+        assert_eq!(8, sides.len());
+        assert!(sides
+            .iter()
+            .all(|side| matches!(side, Some(SheetSide::Tile(..)))));
+
+        let page = PagePixels::new(
+            options.media,
+            Orientation::Portrait,
+            options.resolution_width,
+            options.resolution_height,
+            options.color,
+        );
+        let setup = PageSetup {
+            sides: options.sides,
+            transform: Transform::new(options.sides, options.sheet_back, 1),
+            orientation: Orientation::Portrait,
+            copies: 1,
+            total_pages: sides.len(),
+        };
+        let mut header = Vec::new();
+        write_page_header(Format::Pwg, &page, &setup, &mut header).unwrap();
+        // Duplex, Tumble and FeedTransform of the second tile
+        let field = |at: usize| i32::from_be_bytes(header[at..at + 4].try_into().unwrap());
+        assert_eq!([0, 0, 1], [272, 368, 460].map(field));
+    }
+
+    #[test]
+    fn no_pages_selected() {
+        let options = Options {
+            page_ranges: "10-".parse().unwrap(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            sequence(Format::Pwg, &options, 5),
+            Err(Error::NoPagesSelected)
+        ));
+    }
+
+    #[test]
+    fn password_errors() {
+        let password_error =
+            || PdfiumError::PdfiumLibraryInternalError(PdfiumInternalError::PasswordError);
+        assert!(matches!(
+            load_error(password_error(), None),
+            Error::PasswordRequired
+        ));
+        assert!(matches!(
+            load_error(password_error(), Some("wrong")),
+            Error::WrongPassword
+        ));
+        assert!(matches!(
+            load_error(
+                PdfiumError::PdfiumLibraryInternalError(PdfiumInternalError::FormatError),
+                Some("secret")
+            ),
+            Error::Render(_)
+        ));
+    }
+
+    #[test]
+    fn auto_orientation() {
+        assert_eq!(
+            Orientation::Portrait,
+            Orientation::Auto.resolve(595.0, 842.0, A4)
+        );
+        assert_eq!(
+            Orientation::Rotated,
+            Orientation::Auto.resolve(842.0, 595.0, A4)
+        );
+        assert_eq!(
+            Orientation::Rotated,
+            Orientation::Rotated.resolve(595.0, 842.0, A4)
+        );
+        assert_eq!(
+            Orientation::ReverseLandscape,
+            Orientation::ReverseLandscape.resolve(595.0, 842.0, A4)
+        );
+
+        let two_up = Area { height: 1754, ..A4 };
+        assert_eq!(
+            Orientation::Rotated,
+            Orientation::Auto.resolve(595.0, 842.0, two_up)
+        );
+    }
+
+    #[test]
+    fn two_up_orientation() {
+        let options = Options {
+            number_up: NumberUp::new(2).unwrap(),
+            ..Default::default()
+        };
+        let cell = options
+            .number_up
+            .cells(&PagePixels::a4(), Orientation::Portrait)[0];
+        assert_eq!(
+            Orientation::Rotated,
+            page_orientation(&options).resolve(595.0, 842.0, cell)
+        );
+        assert_eq!(
+            Orientation::Portrait,
+            page_orientation(&options).resolve(842.0, 595.0, cell)
+        );
+    }
+
+    #[test]
+    fn landscape_orientation() {
+        assert_eq!(
+            Orientation::Portrait,
+            Orientation::Landscape.resolve(595.0, 842.0, A4)
+        );
+        assert_eq!(
+            Orientation::Rotated,
+            Orientation::Landscape.resolve(842.0, 595.0, A4)
+        );
     }
 
-    Ok(output)
+    #[test]
+    fn media_below_a_pixel() {
+        let options = Options {
+            media: MediaSize::custom(0.01, 100.0, MediaUnit::Millimetres).unwrap(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            validate(Format::Pwg, &options),
+            Err(Error::InvalidMediaSize)
+        ));
+
+        let options = Options {
+            media: MediaSize::custom(1.0, 100.0, MediaUnit::Millimetres).unwrap(),
+            ..options
+        };
+        assert!(validate(Format::Pwg, &options).is_ok());
+    }
+
+    #[test]
+    fn resolution_bounds() {
+        assert!(matches!(
+            Resolution::new(0),
+            Err(Error::InvalidResolution(0))
+        ));
+        assert!(Resolution::new(Resolution::MAX_DPI).is_ok());
+        assert!(matches!(
+            Resolution::new(Resolution::MAX_DPI + 1),
+            Err(Error::InvalidResolution(9601))
+        ));
+    }
+
+    #[test]
+    fn page_too_large() {
+        let resolution = Resolution::new(Resolution::MAX_DPI).unwrap();
+        let options = Options {
+            media: MediaSize::custom(4e6, 4e6, MediaUnit::Millimetres).unwrap(),
+            resolution_width: resolution,
+            resolution_height: resolution,
+            color: ColorMode::Srgb16,
+            ..Default::default()
+        };
+        assert!(matches!(
+            validate(Format::Pwg, &options),
+            Err(Error::InvalidMediaSize)
+        ));
+
+        // a hundred gigabytes
+        let options = Options {
+            media: MediaSize::IsoA3,
+            ..options
+        };
+        assert!(matches!(
+            validate(Format::Pwg, &options),
+            Err(Error::InvalidMediaSize)
+        ));
+
+        let options = Options {
+            media: MediaSize::custom(841.0, 1189.0, MediaUnit::Millimetres).unwrap(),
+            resolution_width: Resolution::Dpi600,
+            resolution_height: Resolution::Dpi600,
+            ..options
+        };
+        assert!(validate(Format::Pwg, &options).is_ok());
+    }
+
+    #[test]
+    fn urf_resolutions() {
+        let options = Options {
+            resolution_width: Resolution::Dpi600,
+            ..Default::default()
+        };
+        assert!(validate(Format::Pwg, &options).is_ok());
+        assert!(matches!(
+            validate(Format::Urf, &options),
+            Err(Error::UnequalResolutions(600, 300))
+        ));
+    }
 }
@@ -9,69 +9,111 @@ pub fn compress<W>(
 where
     W: Write,
 {
-    let bytes_per_line = (pixel_width * bits_per_pixel + 7) / 8;
+    let bytes_per_line = (pixel_width * bits_per_pixel).div_ceil(8);
+    // runs count whole pixels, or bytes for pixels smaller than a byte
+    let bytes_per_pixel = (bits_per_pixel / 8).max(1);
     let mut lines = bitmap.chunks(bytes_per_line);
 
     if let Some(mut line) = lines.next() {
         let mut count = 1;
         for next in lines {
             if line != next {
-                flush_lines(count, line, compressed)?;
+                flush_lines(count, line, bytes_per_pixel, compressed)?;
                 line = next;
                 count = 0;
             }
             count += 1;
         }
-        flush_lines(count, line, compressed)?;
+        flush_lines(count, line, bytes_per_pixel, compressed)?;
     }
 
     Ok(())
 }
 
-fn flush_lines<W>(mut count: usize, line: &[u8], compressed: &mut W) -> Result<(), Error>
+/// Compresses a bitmap of `height` identical lines, e.g. a blank page, using
+/// line repeats only.
+pub fn compress_repeated<W>(
+    line: &[u8],
+    height: usize,
+    bits_per_pixel: usize,
+    compressed: &mut W,
+) -> Result<(), Error>
+where
+    W: Write,
+{
+    let bytes_per_pixel = (bits_per_pixel / 8).max(1);
+    flush_lines(height, line, bytes_per_pixel, compressed)
+}
+
+fn flush_lines<W>(
+    mut count: usize,
+    line: &[u8],
+    bytes_per_pixel: usize,
+    compressed: &mut W,
+) -> Result<(), Error>
 where
     W: Write,
 {
     while count > 0 {
         let chunk = count.min(256);
         compressed.write_all(&[(chunk - 1) as u8])?;
-        compress_line(line, compressed)?;
+        compress_line(line, bytes_per_pixel, compressed)?;
         count -= chunk;
     }
     Ok(())
 }
 
-fn compress_line<W>(line: &[u8], compressed: &mut W) -> Result<(), Error>
+fn compress_line<W>(line: &[u8], bytes_per_pixel: usize, compressed: &mut W) -> Result<(), Error>
 where
     W: Write,
 {
-    let groups = line.chunk_by(|current, next| current == next);
+    let groups = PixelGroups {
+        line,
+        bytes_per_pixel,
+    };
     let mut index = 0;
     let mut differring_len = None;
 
     for group in groups {
-        if group.len() > 1 {
-            flush_different(line, &mut index, &mut differring_len, compressed)?;
-            flush_rle(&mut index, group, compressed)?;
+        if group.len() > bytes_per_pixel {
+            flush_different(
+                line,
+                bytes_per_pixel,
+                &mut index,
+                &mut differring_len,
+                compressed,
+            )?;
+            flush_rle(&mut index, group, bytes_per_pixel, compressed)?;
         } else if let Some(differring_len) = &mut differring_len {
-            *differring_len += 1;
+            *differring_len += bytes_per_pixel;
         } else {
-            differring_len = Some(1)
+            differring_len = Some(bytes_per_pixel)
         }
     }
     // flush possible remainder
-    flush_different(line, &mut index, &mut differring_len, compressed)?;
+    flush_different(
+        line,
+        bytes_per_pixel,
+        &mut index,
+        &mut differring_len,
+        compressed,
+    )?;
 
     Ok(())
 }
 
-fn flush_rle<W>(index: &mut usize, group: &[u8], compressed: &mut W) -> Result<(), Error>
+fn flush_rle<W>(
+    index: &mut usize,
+    group: &[u8],
+    bytes_per_pixel: usize,
+    compressed: &mut W,
+) -> Result<(), Error>
 where
     W: Write,
 {
-    for chunk in group.chunks(128) {
-        compressed.write_all(&[(chunk.len() - 1) as u8])?;
-        compressed.write_all(&chunk[..1])?;
+    for chunk in group.chunks(128 * bytes_per_pixel) {
+        compressed.write_all(&[(chunk.len() / bytes_per_pixel - 1) as u8])?;
+        compressed.write_all(&chunk[..bytes_per_pixel])?;
     }
     *index += group.len();
 
@@ -80,6 +122,7 @@ where
 
 fn flush_different<W>(
     line: &[u8],
+    bytes_per_pixel: usize,
     index: &mut usize,
     differring_len: &mut Option<usize>,
     compressed: &mut W,
@@ -88,8 +131,8 @@ where
     W: Write,
 {
     if let Some(differring_len) = differring_len.take() {
-        for chunk in line[*index..*index + differring_len].chunks(128) {
-            compressed.write_all(&[(257 - chunk.len()) as u8])?;
+        for chunk in line[*index..*index + differring_len].chunks(128 * bytes_per_pixel) {
+            compressed.write_all(&[(257 - chunk.len() / bytes_per_pixel) as u8])?;
             compressed.write_all(chunk)?;
         }
         *index += differring_len;
@@ -98,6 +141,33 @@ where
     Ok(())
 }
 
+// Like `slice::chunk_by`, but groups runs of equal multi-byte pixels.
+struct PixelGroups<'a> {
+    line: &'a [u8],
+    bytes_per_pixel: usize,
+}
+
+impl<'a> Iterator for PixelGroups<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.line.is_empty() {
+            return None;
+        }
+
+        let pixel_len = self.bytes_per_pixel.min(self.line.len());
+        let pixel = &self.line[..pixel_len];
+        let mut len = pixel_len;
+        while self.line[len..].starts_with(pixel) {
+            len += pixel_len;
+        }
+
+        let (group, rest) = self.line.split_at(len);
+        self.line = rest;
+        Some(group)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,23 +181,23 @@ mod tests {
         let line5 = [0xff_u8, 0xff_u8, 0xff_u8];
 
         let mut output = Vec::with_capacity(8);
-        let _ = compress_line(&line1, &mut output);
+        let _ = compress_line(&line1, 1, &mut output);
         assert_eq!(&[0xfe_u8, 0x8f, 0x78, 0xf7], output.as_slice());
 
         let mut output = Vec::with_capacity(8);
-        let _ = compress_line(&line2, &mut output);
+        let _ = compress_line(&line2, 1, &mut output);
         assert_eq!(&[0xfe_u8, 0x76, 0x77, 0x67], output.as_slice());
 
         let mut output = Vec::with_capacity(8);
-        let _ = compress_line(&line3, &mut output);
+        let _ = compress_line(&line3, 1, &mut output);
         assert_eq!(&[0x02_u8, 0x77], output.as_slice());
 
         let mut output = Vec::with_capacity(8);
-        let _ = compress_line(&line4, &mut output);
+        let _ = compress_line(&line4, 1, &mut output);
         assert_eq!(&[0xfe_u8, 0x8e, 0x38, 0xe3], output.as_slice());
 
         let mut output = Vec::with_capacity(8);
-        let _ = compress_line(&line5, &mut output);
+        let _ = compress_line(&line5, 1, &mut output);
         assert_eq!(&[0x02_u8, 0xff], output.as_slice());
     }
 
@@ -169,4 +239,92 @@ mod tests {
         let _ = compress(&bitmap, 23, 1, &mut output);
         assert_eq!(&expected, output.as_slice());
     }
+
+    #[test]
+    fn rgb_24bit_line() {
+        let line = [
+            0xff_u8, 0x00, 0x00, 0xff, 0x00, 0x00, 0xff, 0x00, 0x00, 0x00, 0xff, 0x00, 0x00, 0x00,
+            0xff,
+        ];
+
+        let mut output = Vec::with_capacity(16);
+        let _ = compress_line(&line, 3, &mut output);
+        assert_eq!(
+            &[0x02_u8, 0xff, 0x00, 0x00, 0xff, 0x00, 0xff, 0x00, 0x00, 0x00, 0xff],
+            output.as_slice()
+        );
+    }
+
+    #[test]
+    fn rgb_24bit_bytes_equal_across_pixels() {
+        // equal bytes spanning pixel boundaries must not form a run
+        let line = [0x00_u8, 0x00, 0xff, 0xff, 0x00, 0x00];
+
+        let mut output = Vec::with_capacity(8);
+        let _ = compress_line(&line, 3, &mut output);
+        assert_eq!(
+            &[0xff_u8, 0x00, 0x00, 0xff, 0xff, 0x00, 0x00],
+            output.as_slice()
+        );
+    }
+
+    #[test]
+    fn rgb_24bit_long_run() {
+        let line = [0x12_u8, 0x34, 0x56].repeat(130);
+
+        let mut output = Vec::with_capacity(8);
+        let _ = compress_line(&line, 3, &mut output);
+        assert_eq!(
+            &[0x7f_u8, 0x12, 0x34, 0x56, 0x01, 0x12, 0x34, 0x56],
+            output.as_slice()
+        );
+    }
+
+    #[test]
+    fn rgb_24bit_bitmap() {
+        let white = [0xff_u8; 3];
+        let black = [0x00_u8; 3];
+        let bitmap = [white, white, white, white, black, white, white, white]
+            .iter()
+            .chain([white; 8].iter())
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+
+        let expected = [
+            0x0_u8, 0x03, 0xff, 0xff, 0xff, 0x0, 0x00, 0x00, 0x00, 0x00, 0x02, 0xff, 0xff, 0xff,
+            0x1, 0x03, 0xff, 0xff, 0xff,
+        ];
+
+        let mut output = Vec::with_capacity(32);
+        let _ = compress(&bitmap, 4, 24, &mut output);
+        assert_eq!(&expected, output.as_slice());
+    }
+
+    #[test]
+    fn gray_16bit_line() {
+        let line = [0x12_u8, 0x34, 0x12, 0x34, 0x34, 0x12];
+
+        let mut output = Vec::with_capacity(8);
+        let _ = compress_line(&line, 2, &mut output);
+        assert_eq!(&[0x01_u8, 0x12, 0x34, 0x00, 0x34, 0x12], output.as_slice());
+    }
+
+    #[test]
+    fn repeated_line() {
+        let line = [0xff_u8; 6];
+        let mut output = Vec::new();
+        let _ = compress_repeated(&line, 600, 24, &mut output);
+        assert_eq!(
+            &[
+                0xff_u8, 0x01, 0xff, 0xff, 0xff, 0xff, 0x01, 0xff, 0xff, 0xff, 0x57, 0x01, 0xff,
+                0xff, 0xff
+            ],
+            output.as_slice()
+        );
+
+        let mut bitmap_output = Vec::new();
+        let _ = compress(&[0xff_u8; 6 * 600], 2, 24, &mut bitmap_output);
+        assert_eq!(bitmap_output, output);
+    }
 }
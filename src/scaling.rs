@@ -0,0 +1,158 @@
+/// How pages are scaled onto the media, IPP `print-scaling`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Scaling {
+    /// Fits pages larger than the media, fills the media with pages that need
+    /// at most 10% enlarging to do so and prints smaller pages at actual size.
+    /// The output is borderless, so unlike IPP `auto` the choice does not
+    /// depend on the media margins.
+    Auto,
+    /// Fits pages larger than the media, prints smaller pages at actual size.
+    #[default]
+    AutoFit,
+    /// Scales pages to fit the media, keeping their aspect ratio.
+    Fit,
+    /// Scales pages to fill the media, cropping what does not fit.
+    Fill,
+    /// Prints pages at actual size, cropping what does not fit.
+    None,
+}
+
+/// Rectangle on the raster, in pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Area {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// Scale of a page relative to its actual size and the raster position of its
+/// top left corner, which is negative for cropped pages.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Placement {
+    pub scale: f32,
+    pub x: isize,
+    pub y: isize,
+}
+
+impl Scaling {
+    /// Centres a page of `width` by `height` pixels at actual size on `area`.
+    pub(crate) fn place(self, width: f32, height: f32, area: Area) -> Placement {
+        let fit = (area.width as f32 / width).min(area.height as f32 / height);
+        let fill = (area.width as f32 / width).max(area.height as f32 / height);
+        let scale = match self {
+            Scaling::Auto if fit < 1.0 => fit,
+            Scaling::Auto if fill <= 1.1 => fill,
+            Scaling::Auto => 1.0,
+            Scaling::AutoFit => fit.min(1.0),
+            Scaling::Fit => fit,
+            Scaling::Fill => fill,
+            Scaling::None => 1.0,
+        };
+
+        let centre = |start: usize, available: usize, size: f32| {
+            start as isize + (available as isize - (size * scale).round() as isize) / 2
+        };
+
+        Placement {
+            scale,
+            x: centre(area.x, area.width, width),
+            y: centre(area.y, area.height, height),
+        }
+    }
+}
+
+impl Placement {
+    /// Part of `area` covered by a page of `width` by `height` pixels placed
+    /// here, `None` if the page is entirely outside.
+    pub(crate) fn visible(&self, width: usize, height: usize, area: Area) -> Option<Area> {
+        let left = self.x.max(area.x as isize);
+        let right = (self.x + width as isize).min((area.x + area.width) as isize);
+        let top = self.y.max(area.y as isize);
+        let bottom = (self.y + height as isize).min((area.y + area.height) as isize);
+
+        (left < right && top < bottom).then(|| Area {
+            x: left as usize,
+            y: top as usize,
+            width: (right - left) as usize,
+            height: (bottom - top) as usize,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A4: Area = Area {
+        x: 0,
+        y: 0,
+        width: 2480,
+        height: 3508,
+    };
+
+    #[test]
+    fn fit() {
+        let placement = Scaling::Fit.place(1240.0, 1240.0, A4);
+        assert_eq!(2.0, placement.scale);
+        assert_eq!((0, 514), (placement.x, placement.y));
+    }
+
+    #[test]
+    fn fill() {
+        let placement = Scaling::Fill.place(1240.0, 1240.0, A4);
+        assert_eq!(3508.0 / 1240.0, placement.scale);
+        assert_eq!((-514, 0), (placement.x, placement.y));
+    }
+
+    #[test]
+    fn actual_size() {
+        let placement = Scaling::None.place(2550.0, 3300.0, A4);
+        assert_eq!(1.0, placement.scale);
+        assert_eq!((-35, 104), (placement.x, placement.y));
+    }
+
+    #[test]
+    fn visible() {
+        let placement = Scaling::Fill.place(1240.0, 1240.0, A4);
+        assert_eq!(Some(A4), placement.visible(3508, 3508, A4));
+
+        let placement = Scaling::None.place(2550.0, 3300.0, A4);
+        assert_eq!(
+            Some(Area {
+                x: 0,
+                y: 104,
+                width: 2480,
+                height: 3300
+            }),
+            placement.visible(2550, 3300, A4)
+        );
+        assert_eq!(None, placement.visible(2550, 3300, Area { x: 2600, ..A4 }));
+    }
+
+    #[test]
+    fn auto_fit() {
+        assert_eq!(1.0, Scaling::AutoFit.place(1240.0, 1240.0, A4).scale);
+        assert_eq!(
+            Scaling::Fit.place(2550.0, 3300.0, A4),
+            Scaling::AutoFit.place(2550.0, 3300.0, A4)
+        );
+    }
+
+    #[test]
+    fn auto() {
+        // Letter is wider than A4
+        assert_eq!(
+            Scaling::Fit.place(2550.0, 3300.0, A4),
+            Scaling::Auto.place(2550.0, 3300.0, A4)
+        );
+        // A5 is half of A4
+        assert_eq!(1.0, Scaling::Auto.place(1748.0, 2480.0, A4).scale);
+        // a page a few millimetres smaller than the media
+        assert_eq!(
+            Scaling::Fill.place(2400.0, 3400.0, A4),
+            Scaling::Auto.place(2400.0, 3400.0, A4)
+        );
+        assert!(Scaling::Auto.place(2400.0, 3400.0, A4).scale > 1.0);
+    }
+}
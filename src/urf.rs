@@ -1,7 +1,7 @@
 use std::{io::Write, mem::size_of, slice::from_raw_parts};
 
 mod types {
-    #[allow(dead_code)]
+    #[allow(dead_code, clippy::upper_case_acronyms)]
     #[repr(u8)]
     pub enum ColorSpace {
         Sgray = 0_u8.to_be(),
@@ -13,7 +13,7 @@ mod types {
         CMYK = 6_u8.to_be(),
     }
 
-    #[allow(dead_code)]
+    #[allow(dead_code, clippy::enum_variant_names)]
     #[repr(u8)]
     pub enum Duplex {
         NoDuplex = 1_u8.to_be(),
@@ -76,7 +76,7 @@ mod types {
         Roll10 = 49_u8.to_be(),
     }
 
-    #[allow(dead_code)]
+    #[allow(dead_code, clippy::enum_variant_names)]
     #[repr(u8)]
     pub enum MediaType {
         AutomaticMediaType = 0_u8.to_be(),
@@ -115,7 +115,12 @@ mod types {
 
 use types::*;
 
-use crate::{error::Error, render::A4Pixels};
+use crate::{
+    color::ColorMode,
+    duplex::Sides,
+    error::Error,
+    render::{PagePixels, PageSetup},
+};
 
 const URF_SYNC_WORD: &[u8] = b"UNIRAST\0";
 
@@ -136,11 +141,15 @@ struct PageHeader {
 }
 
 impl PageHeader {
-    pub fn new(page_pixels: &A4Pixels) -> Self {
+    pub fn new(page_pixels: &PagePixels, setup: &PageSetup) -> Self {
         Self {
             BitsPerPixel: (page_pixels.bits_per_pixel as u8).to_be(),
-            ColorSpace: ColorSpace::Sgray,
-            Duplex: Duplex::NoDuplex,                 // TODO?
+            ColorSpace: color_space(page_pixels.color).unwrap_or(ColorSpace::Sgray),
+            Duplex: match setup.sides {
+                Sides::OneSided => Duplex::NoDuplex,
+                Sides::TwoSidedLongEdge => Duplex::LongSide,
+                Sides::TwoSidedShortEdge => Duplex::ShortSide,
+            },
             Quality: Quality::Default,                // TODO
             MediaType: MediaType::AutomaticMediaType, // TODO
             MediaPosition: MediaPosition::Auto,       // TODO
@@ -157,8 +166,26 @@ impl PageHeader {
     }
 }
 
+pub(crate) fn supports(color: ColorMode) -> bool {
+    color_space(color).is_some()
+}
+
+fn color_space(color: ColorMode) -> Option<ColorSpace> {
+    match color {
+        ColorMode::Sgray8 => Some(ColorSpace::Sgray),
+        ColorMode::Srgb8 | ColorMode::Srgb16 => Some(ColorSpace::Srgb),
+        ColorMode::Cmyk8(_) => Some(ColorSpace::CMYK),
+        ColorMode::AdobeRgb8 | ColorMode::AdobeRgb16 => Some(ColorSpace::AdobeRgb),
+        ColorMode::Rgb8 | ColorMode::Rgb16 => Some(ColorSpace::RGB),
+        // URF has neither bilevel, black nor 16 bits per pixel rasters
+        ColorMode::Sgray1(_) | ColorMode::Black8 | ColorMode::Black1(_) | ColorMode::Sgray16 => {
+            None
+        }
+    }
+}
+
 pub(crate) fn write_file_header(
-    _: &A4Pixels,
+    _: &PagePixels,
     pages: u32,
     writer: &mut impl Write,
 ) -> Result<(), Error> {
@@ -167,8 +194,12 @@ pub(crate) fn write_file_header(
     Ok(())
 }
 
-pub(crate) fn write_page_header(pixels: &A4Pixels, writer: &mut impl Write) -> Result<(), Error> {
-    writer.write_all(PageHeader::new(pixels).as_slice())?;
+pub(crate) fn write_page_header(
+    pixels: &PagePixels,
+    setup: &PageSetup,
+    writer: &mut impl Write,
+) -> Result<(), Error> {
+    writer.write_all(PageHeader::new(pixels, setup).as_slice())?;
     Ok(())
 }
 
@@ -1,45 +1,732 @@
-use std::{fs, sync::Arc};
+use std::{fs, path::PathBuf, sync::Arc};
 
+use futures_lite::StreamExt;
 use macro_rules_attribute::apply;
-use pdf2pwg::{render, Error, Format, Resolution};
+use pdf2pwg::{
+    render, render_stream, render_to_async_writer, render_to_writer, BlackGeneration, Collation,
+    ColorMode, Copies, Error, Format, Halftone, MediaSize, NumberUp, Options, Orientation, Poster,
+    Renderer, Resolution, Scaling, SheetBack, Sides,
+};
+use pdfium_render::prelude::PdfiumError;
 use smol_macros::test;
 
-#[apply(test!)]
-async fn render_file_pwg() -> Result<(), Error> {
-    let pdf = fs::read(r"D:\Work\DancesportServices\pdf2pwg\tests\test.pdf").unwrap();
-    let rendered = render(
-        Arc::new(pdf),
-        Resolution::Dpi600,
-        Resolution::Dpi600,
-        Format::Pwg,
+// the tests that render need pdfium, which is loaded at runtime, and are
+// ignored unless run with `cargo test -- --ignored`
+
+// byte offsets of the PWG page header fields, all big-endian 32-bit
+const PWG_HEADER_LEN: usize = 1796;
+const PWG_DUPLEX: usize = 272;
+const PWG_HW_RESOLUTION: usize = 276;
+const PWG_NUM_COPIES: usize = 340;
+const PWG_ORIENTATION: usize = 344;
+const PWG_TUMBLE: usize = 368;
+const PWG_WIDTH: usize = 372;
+const PWG_HEIGHT: usize = 376;
+const PWG_BITS_PER_COLOR: usize = 384;
+const PWG_BITS_PER_PIXEL: usize = 388;
+const PWG_COLOR_SPACE: usize = 400;
+const PWG_NUM_COLORS: usize = 420;
+const PWG_TOTAL_PAGE_COUNT: usize = 452;
+const PWG_PAGE_SIZE_NAME: usize = 1732;
+
+// byte offsets of the URF page header fields
+const URF_HEADER_LEN: usize = 32;
+const URF_BITS_PER_PIXEL: usize = 0;
+const URF_COLOR_SPACE: usize = 1;
+const URF_DUPLEX: usize = 2;
+const URF_WIDTH: usize = 12;
+const URF_HEIGHT: usize = 16;
+const URF_HW_RES: usize = 20;
+
+/// Header of an output page and whether its raster is a single colour.
+struct Page {
+    format: Format,
+    header: Vec<u8>,
+    blank: bool,
+}
+
+impl Page {
+    fn pwg(&self, offset: usize) -> u32 {
+        assert_eq!(Format::Pwg, self.format);
+        u32::from_be_bytes(self.header[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn pwg_string(&self, offset: usize) -> &str {
+        let field = &self.header[offset..offset + 64];
+        let len = field.iter().position(|&byte| byte == 0).unwrap_or(64);
+        std::str::from_utf8(&field[..len]).unwrap()
+    }
+
+    fn urf(&self, offset: usize) -> u32 {
+        assert_eq!(Format::Urf, self.format);
+        match offset {
+            URF_WIDTH | URF_HEIGHT | URF_HW_RES => {
+                u32::from_be_bytes(self.header[offset..offset + 4].try_into().unwrap())
+            }
+            _ => self.header[offset] as u32,
+        }
+    }
+
+    fn width(&self) -> u32 {
+        match self.format {
+            Format::Pwg => self.pwg(PWG_WIDTH),
+            Format::Urf => self.urf(URF_WIDTH),
+        }
+    }
+
+    fn height(&self) -> u32 {
+        match self.format {
+            Format::Pwg => self.pwg(PWG_HEIGHT),
+            Format::Urf => self.urf(URF_HEIGHT),
+        }
+    }
+
+    fn bits_per_pixel(&self) -> u32 {
+        match self.format {
+            Format::Pwg => self.pwg(PWG_BITS_PER_PIXEL),
+            Format::Urf => self.urf(URF_BITS_PER_PIXEL),
+        }
+    }
+}
+
+/// Splits the output into pages, decoding each raster to check its length.
+fn parse(format: Format, output: &[u8]) -> Vec<Page> {
+    let (header_len, mut rest) = match format {
+        Format::Pwg => {
+            assert_eq!(b"RaS2", &output[..4]);
+            (PWG_HEADER_LEN, &output[4..])
+        }
+        Format::Urf => {
+            assert_eq!(b"UNIRAST\0", &output[..8]);
+            (URF_HEADER_LEN, &output[12..])
+        }
+    };
+
+    let mut pages = Vec::new();
+    while !rest.is_empty() {
+        let (header, mut data) = rest.split_at(header_len);
+        let mut page = Page {
+            format,
+            header: header.to_vec(),
+            blank: true,
+        };
+        let bytes_per_line = (page.width() * page.bits_per_pixel()).div_ceil(8) as usize;
+        let bytes_per_pixel = (page.bits_per_pixel() as usize / 8).max(1);
+
+        let mut lines = 0;
+        let mut first_byte = None;
+        while lines < page.height() as usize {
+            lines += data[0] as usize + 1;
+            data = &data[1..];
+
+            let mut line = Vec::with_capacity(bytes_per_line);
+            while line.len() < bytes_per_line {
+                let control = data[0] as usize;
+                let len = match control {
+                    0..128 => bytes_per_pixel,
+                    _ => (257 - control) * bytes_per_pixel,
+                };
+                let (pixels, next) = data[1..].split_at(len);
+                match control {
+                    0..128 => (0..=control).for_each(|_| line.extend_from_slice(pixels)),
+                    _ => line.extend_from_slice(pixels),
+                }
+                data = next;
+            }
+            assert_eq!(bytes_per_line, line.len());
+
+            let first_byte = *first_byte.get_or_insert(line[0]);
+            page.blank &= line.iter().all(|&byte| byte == first_byte);
+        }
+        assert_eq!(page.height() as usize, lines);
+
+        rest = data;
+        pages.push(page);
+    }
+
+    if format == Format::Urf {
+        let page_count = u32::from_be_bytes(output[8..12].try_into().unwrap());
+        assert_eq!(page_count as usize, pages.len());
+    }
+    pages
+}
+
+fn fixture(name: &str) -> Vec<u8> {
+    fs::read(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join(name),
     )
-    .await?;
+    .unwrap()
+}
+
+fn test_pdf() -> Vec<u8> {
+    fixture("test.pdf")
+}
+
+/// Renders the test PDF, see [`render_pdf`].
+async fn render_file(
+    name: &str,
+    format: Format,
+    options: Options,
+    check: impl FnOnce(&[Page]),
+) -> Result<(), Error> {
+    render_pdf(test_pdf(), name, format, options, check).await
+}
 
+/// Renders a PDF, keeps the output in the target directory for a look and
+/// checks its pages.
+async fn render_pdf(
+    pdf: Vec<u8>,
+    name: &str,
+    format: Format,
+    options: Options,
+    check: impl FnOnce(&[Page]),
+) -> Result<(), Error> {
+    let rendered = render(Arc::new(pdf), format, options).await?;
     fs::write(
-        format!(r"D:\Work\DancesportServices\pdf2pwg\target\debug\test.pwg"),
-        rendered,
+        PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name),
+        &rendered,
     )
     .unwrap();
-
+    check(&parse(format, &rendered));
     Ok(())
 }
 
+/// Renders a PDF that must fail.
+async fn render_error(pdf: Vec<u8>, options: Options) -> Error {
+    match render(Arc::new(pdf), Format::Pwg, options).await {
+        Ok(_) => panic!("rendered without an error"),
+        Err(error) => error,
+    }
+}
+
+fn dpi600() -> Options {
+    Options {
+        resolution_width: Resolution::Dpi600,
+        resolution_height: Resolution::Dpi600,
+        ..Default::default()
+    }
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg() -> Result<(), Error> {
+    render_file("test.pwg", Format::Pwg, dpi600(), |pages| {
+        assert_eq!(1, pages.len());
+        let page = &pages[0];
+        assert!(!page.blank);
+        assert_eq!((4961, 7016), (page.width(), page.height()));
+        assert_eq!(600, page.pwg(PWG_HW_RESOLUTION));
+        assert_eq!(600, page.pwg(PWG_HW_RESOLUTION + 4));
+        assert_eq!(8, page.pwg(PWG_BITS_PER_PIXEL));
+        assert_eq!(18, page.pwg(PWG_COLOR_SPACE));
+        assert_eq!(1, page.pwg(PWG_NUM_COPIES));
+        assert_eq!(1, page.pwg(PWG_TOTAL_PAGE_COUNT));
+        assert_eq!(0, page.pwg(PWG_DUPLEX));
+        assert_eq!("iso_a4_210x297mm", page.pwg_string(PWG_PAGE_SIZE_NAME));
+    })
+    .await
+}
+
 #[apply(test!)]
+#[ignore = "needs pdfium"]
 async fn render_file_urf() -> Result<(), Error> {
-    let pdf = fs::read(r"D:\Work\DancesportServices\pdf2pwg\tests\test.pdf").unwrap();
-    let rendered = render(
-        Arc::new(pdf),
-        Resolution::Dpi600,
-        Resolution::Dpi600,
-        Format::Urf,
+    render_file("test.urf", Format::Urf, dpi600(), |pages| {
+        assert_eq!(1, pages.len());
+        let page = &pages[0];
+        assert!(!page.blank);
+        assert_eq!((4961, 7016), (page.width(), page.height()));
+        assert_eq!(600, page.urf(URF_HW_RES));
+        assert_eq!(8, page.urf(URF_BITS_PER_PIXEL));
+        assert_eq!(0, page.urf(URF_COLOR_SPACE));
+        assert_eq!(1, page.urf(URF_DUPLEX));
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_letter() -> Result<(), Error> {
+    let options = Options {
+        media: MediaSize::NaLetter,
+        ..Default::default()
+    };
+    render_file("test-letter.pwg", Format::Pwg, options, |pages| {
+        assert_eq!((2550, 3300), (pages[0].width(), pages[0].height()));
+        assert_eq!(
+            "na_letter_8.5x11in",
+            pages[0].pwg_string(PWG_PAGE_SIZE_NAME)
+        );
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_360dpi() -> Result<(), Error> {
+    let resolution = Resolution::new(360)?;
+    let options = Options {
+        resolution_width: resolution,
+        resolution_height: resolution,
+        ..Default::default()
+    };
+    render_file("test-360dpi.pwg", Format::Pwg, options, |pages| {
+        assert_eq!((2976, 4209), (pages[0].width(), pages[0].height()));
+        assert_eq!(360, pages[0].pwg(PWG_HW_RESOLUTION));
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_urf_srgb() -> Result<(), Error> {
+    let options = Options {
+        color: ColorMode::Srgb8,
+        ..Default::default()
+    };
+    render_file("test-srgb.urf", Format::Urf, options, |pages| {
+        assert_eq!(24, pages[0].urf(URF_BITS_PER_PIXEL));
+        assert_eq!(1, pages[0].urf(URF_COLOR_SPACE));
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_bilevel() -> Result<(), Error> {
+    let options = Options {
+        color: ColorMode::Sgray1(Halftone::FloydSteinberg),
+        ..dpi600()
+    };
+    render_file("test-bilevel.pwg", Format::Pwg, options, |pages| {
+        assert!(!pages[0].blank);
+        assert_eq!(1, pages[0].pwg(PWG_BITS_PER_COLOR));
+        assert_eq!(1, pages[0].pwg(PWG_BITS_PER_PIXEL));
+        assert_eq!(18, pages[0].pwg(PWG_COLOR_SPACE));
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_black() -> Result<(), Error> {
+    let options = Options {
+        color: ColorMode::Black8,
+        ..Default::default()
+    };
+    render_file("test-black.pwg", Format::Pwg, options, |pages| {
+        assert_eq!(8, pages[0].pwg(PWG_BITS_PER_PIXEL));
+        assert_eq!(3, pages[0].pwg(PWG_COLOR_SPACE));
+    })
+    .await
+}
+
+#[apply(test!)]
+async fn render_file_urf_black_unsupported() {
+    let options = Options {
+        color: ColorMode::Black8,
+        ..Default::default()
+    };
+    let rendered = render(Arc::new(test_pdf()), Format::Urf, options).await;
+
+    assert!(matches!(rendered, Err(Error::UnsupportedColorMode(_))));
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_cmyk() -> Result<(), Error> {
+    let options = Options {
+        color: ColorMode::Cmyk8(BlackGeneration::default()),
+        ..Default::default()
+    };
+    render_file("test-cmyk.pwg", Format::Pwg, options, |pages| {
+        assert_eq!(32, pages[0].pwg(PWG_BITS_PER_PIXEL));
+        assert_eq!(6, pages[0].pwg(PWG_COLOR_SPACE));
+        assert_eq!(4, pages[0].pwg(PWG_NUM_COLORS));
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_srgb16() -> Result<(), Error> {
+    let options = Options {
+        color: ColorMode::Srgb16,
+        ..Default::default()
+    };
+    render_file("test-srgb16.pwg", Format::Pwg, options, |pages| {
+        assert_eq!(16, pages[0].pwg(PWG_BITS_PER_COLOR));
+        assert_eq!(48, pages[0].pwg(PWG_BITS_PER_PIXEL));
+        assert_eq!(19, pages[0].pwg(PWG_COLOR_SPACE));
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_urf_adobe_rgb() -> Result<(), Error> {
+    let options = Options {
+        color: ColorMode::AdobeRgb8,
+        ..Default::default()
+    };
+    render_file("test-adobe-rgb.urf", Format::Urf, options, |pages| {
+        assert_eq!(24, pages[0].urf(URF_BITS_PER_PIXEL));
+        assert_eq!(3, pages[0].urf(URF_COLOR_SPACE));
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_duplex() -> Result<(), Error> {
+    let options = Options {
+        sides: Sides::TwoSidedLongEdge,
+        sheet_back: SheetBack::Rotated,
+        ..Default::default()
+    };
+    render_file("test-duplex.pwg", Format::Pwg, options, |pages| {
+        assert_eq!(1, pages.len());
+        assert_eq!(1, pages[0].pwg(PWG_DUPLEX));
+        assert_eq!(0, pages[0].pwg(PWG_TUMBLE));
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_urf_duplex_padded() -> Result<(), Error> {
+    let options = Options {
+        sides: Sides::TwoSidedShortEdge,
+        pad_odd_pages: true,
+        ..Default::default()
+    };
+    render_file("test-duplex-padded.urf", Format::Urf, options, |pages| {
+        assert_eq!(2, pages.len());
+        assert!(!pages[0].blank);
+        assert!(pages[1].blank);
+        assert_eq!(2, pages[1].urf(URF_DUPLEX));
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_uncollated() -> Result<(), Error> {
+    let options = Options {
+        sides: Sides::TwoSidedLongEdge,
+        copies: Copies::new(2, Collation::Uncollated)?,
+        ..Default::default()
+    };
+    render_file("test-uncollated.pwg", Format::Pwg, options, |pages| {
+        // each copy of the single page gets a sheet of its own
+        let blank = pages.iter().map(|page| page.blank).collect::<Vec<_>>();
+        assert_eq!(vec![false, true, false, true], blank);
+        assert_eq!(1, pages[0].pwg(PWG_NUM_COPIES));
+        assert_eq!(4, pages[0].pwg(PWG_TOTAL_PAGE_COUNT));
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_urf_page_ranges() -> Result<(), Error> {
+    let options = Options {
+        page_ranges: "1,3-".parse()?,
+        ..Default::default()
+    };
+    render_file("test-page-ranges.urf", Format::Urf, options, |pages| {
+        assert_eq!(1, pages.len());
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_no_pages_selected() -> Result<(), Error> {
+    let options = Options {
+        page_ranges: "2-".parse()?,
+        ..Default::default()
+    };
+    let error = render_error(test_pdf(), options).await;
+    assert!(matches!(error, Error::NoPagesSelected));
+    Ok(())
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_letter_fill() -> Result<(), Error> {
+    let options = Options {
+        media: MediaSize::NaLetter,
+        scaling: Scaling::Fill,
+        ..Default::default()
+    };
+    render_file("test-letter-fill.pwg", Format::Pwg, options, |pages| {
+        assert!(!pages[0].blank);
+        assert_eq!((2550, 3300), (pages[0].width(), pages[0].height()));
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_a5_actual_size() -> Result<(), Error> {
+    // only the middle of the A4 page is rendered
+    let options = Options {
+        media: MediaSize::IsoA5,
+        scaling: Scaling::None,
+        ..Default::default()
+    };
+    render_file("test-a5-actual-size.pwg", Format::Pwg, options, |pages| {
+        assert_eq!(1, pages.len());
+        assert_eq!((1748, 2480), (pages[0].width(), pages[0].height()));
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_auto_orientation() -> Result<(), Error> {
+    let options = Options {
+        orientation: Orientation::Auto,
+        ..Default::default()
+    };
+    render_file("test-auto-orientation.pwg", Format::Pwg, options, |pages| {
+        // the test page is portrait
+        assert_eq!(0, pages[0].pwg(PWG_ORIENTATION));
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_landscape() -> Result<(), Error> {
+    let options = Options {
+        orientation: Orientation::Landscape,
+        ..Default::default()
+    };
+    render_file("test-landscape.pwg", Format::Pwg, options, |pages| {
+        // only landscape pages are rotated
+        assert_eq!(0, pages[0].pwg(PWG_ORIENTATION));
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_rotated() -> Result<(), Error> {
+    let options = Options {
+        orientation: Orientation::Rotated,
+        ..Default::default()
+    };
+    render_file("test-rotated.pwg", Format::Pwg, options, |pages| {
+        assert_eq!(1, pages[0].pwg(PWG_ORIENTATION));
+        assert_eq!((2480, 3508), (pages[0].width(), pages[0].height()));
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_reverse_landscape() -> Result<(), Error> {
+    let options = Options {
+        orientation: Orientation::ReverseLandscape,
+        ..Default::default()
+    };
+    render_file(
+        "test-reverse-landscape.pwg",
+        Format::Pwg,
+        options,
+        |pages| {
+            assert_eq!(3, pages[0].pwg(PWG_ORIENTATION));
+            assert_eq!((2480, 3508), (pages[0].width(), pages[0].height()));
+        },
+    )
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_two_up() -> Result<(), Error> {
+    let options = Options {
+        number_up: NumberUp::new(2)?,
+        ..Default::default()
+    };
+    render_file("test-two-up.pwg", Format::Pwg, options, |pages| {
+        // the portrait page is rotated into the landscape cell
+        assert_eq!(1, pages[0].pwg(PWG_ORIENTATION));
+        assert!(!pages[0].blank);
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_four_up() -> Result<(), Error> {
+    let mut number_up = NumberUp::new(4)?;
+    number_up.border = true;
+    number_up.gutter = 5.0;
+    let options = Options {
+        number_up,
+        ..Default::default()
+    };
+    render_file("test-four-up.pwg", Format::Pwg, options, |pages| {
+        assert_eq!(1, pages.len());
+        assert!(!pages[0].blank);
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_booklet() -> Result<(), Error> {
+    let options = Options {
+        booklet: true,
+        ..Default::default()
+    };
+    render_file("test-booklet.pwg", Format::Pwg, options, |pages| {
+        // the single page padded to four, two on each side of one sheet
+        assert_eq!(2, pages.len());
+        assert!(!pages[0].blank);
+        assert!(pages[1].blank);
+        assert_eq!(1, pages[0].pwg(PWG_TUMBLE));
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_poster() -> Result<(), Error> {
+    let options = Options {
+        media: MediaSize::IsoA5,
+        poster: Some(Poster {
+            overlap: 10.0,
+            crop_marks: true,
+        }),
+        ..Default::default()
+    };
+    render_file("test-poster.pwg", Format::Pwg, options, |pages| {
+        // an A4 page takes two by two A5 sheets
+        assert_eq!(4, pages.len());
+        assert_eq!((1748, 2480), (pages[0].width(), pages[0].height()));
+        assert_eq!(4, pages[0].pwg(PWG_TOTAL_PAGE_COUNT));
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_to_writers() -> Result<(), Error> {
+    let rendered = render(Arc::new(test_pdf()), Format::Pwg, Options::default()).await?;
+
+    let written = render_to_writer(
+        Arc::new(test_pdf()),
+        Format::Pwg,
+        Options::default(),
+        Vec::new(),
     )
     .await?;
+    assert_eq!(rendered, written);
 
-    fs::write(
-        format!(r"D:\Work\DancesportServices\pdf2pwg\target\debug\test.urf"),
-        rendered,
+    let mut written = Vec::new();
+    render_to_async_writer(
+        Arc::new(test_pdf()),
+        Format::Pwg,
+        Options::default(),
+        &mut written,
     )
-    .unwrap();
+    .await?;
+    assert_eq!(rendered, written);
+    Ok(())
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_urf_stream() -> Result<(), Error> {
+    let mut chunks = render_stream(Arc::new(test_pdf()), Format::Urf, Options::default());
+
+    let header = chunks.next().await.unwrap()?;
+    assert_eq!(12, header.len());
+
+    // a chunk per page
+    let mut output = header.clone();
+    let mut chunk_count = 0;
+    while let Some(page) = chunks.next().await {
+        output.extend(page?);
+        chunk_count += 1;
+    }
+    assert_eq!(1, chunk_count);
+    assert_eq!(1, parse(Format::Urf, &output).len());
+    Ok(())
+}
 
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_renderer() -> Result<(), Error> {
+    let renderer = Renderer::new()?;
+
+    let pdf = Arc::new(test_pdf());
+    let cloned = renderer.clone();
+    let first = renderer.render(pdf.clone(), Format::Pwg, Options::default());
+    let second = cloned.render(pdf, Format::Pwg, Options::default());
+    let (first, second) = futures_lite::future::zip(first, second).await;
+    let (first, second) = (first?, second?);
+    assert_eq!(first, second);
+    assert_eq!(1, parse(Format::Pwg, &first).len());
     Ok(())
 }
+
+#[test]
+fn renderer_with_missing_library() {
+    assert!(matches!(
+        Renderer::with_library("/nonexistent/libpdfium.so"),
+        Err(Error::Render(PdfiumError::LoadLibraryError(_)) | Error::StaticallyLinked)
+    ));
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_password() -> Result<(), Error> {
+    let options = Options {
+        password: Some("secret".to_string()),
+        ..Default::default()
+    };
+    let pdf = fixture("encrypted.pdf");
+    render_pdf(pdf, "test-encrypted.pwg", Format::Pwg, options, |pages| {
+        assert_eq!(1, pages.len());
+        assert!(!pages[0].blank);
+    })
+    .await
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_password_required() {
+    let error = render_error(fixture("encrypted.pdf"), Options::default()).await;
+    assert!(matches!(error, Error::PasswordRequired));
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_wrong_password() {
+    let options = Options {
+        password: Some("wrong".to_string()),
+        ..Default::default()
+    };
+    let error = render_error(fixture("encrypted.pdf"), options).await;
+    assert!(matches!(error, Error::WrongPassword));
+}
+
+#[apply(test!)]
+#[ignore = "needs pdfium"]
+async fn render_file_pwg_password_unused() -> Result<(), Error> {
+    // the test PDF is not encrypted, so any password opens it
+    let options = Options {
+        password: Some("secret".to_string()),
+        ..Default::default()
+    };
+    render_file("test-password.pwg", Format::Pwg, options, |pages| {
+        assert_eq!(1, pages.len());
+    })
+    .await
+}